@@ -1,6 +1,5 @@
-use crate::util::chunk_sizes::ChunkSizes;
-use crate::util::chunk_stream::Chunk;
-use crate::util::sha256;
+use cdc_comparison::util::boundary::Sha256Digest;
+use cdc_comparison::{ChunkSizes, HashedBoundary};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
@@ -8,7 +7,7 @@ use std::time::{Duration, Instant};
 pub struct AlgorithmResult {
     name: String,
     chunk_sizes: ChunkSizes,
-    chunks: HashMap<String, usize>,
+    chunks: HashMap<Sha256Digest, usize>,
     total_size: usize,
     chunk_count: usize,
     start: Instant,
@@ -49,10 +48,9 @@ impl AlgorithmResult {
         }
     }
 
-    pub fn append_chunk(&mut self, chunk: Chunk) {
+    pub fn append_chunk(&mut self, chunk: HashedBoundary) {
         self.total_size += chunk.length;
-        let sha = sha256(&chunk.data);
-        let new_is_duplicate = self.chunks.insert(sha, chunk.length).is_some();
+        let new_is_duplicate = self.chunks.insert(chunk.digest, chunk.length).is_some();
 
         match (self.current_interval_duplicate, new_is_duplicate) {
             (false, false) | (true, true) => {
@@ -117,11 +115,11 @@ impl AlgorithmResult {
     }
 
     pub fn min_interval_size(&self) -> f64 {
-        self.interval_sizes.iter().min().map(|v| *v).unwrap() as f64
+        self.interval_sizes.iter().min().copied().unwrap() as f64
     }
 
     pub fn max_interval_size(&self) -> f64 {
-        self.interval_sizes.iter().max().map(|v| *v).unwrap() as f64
+        self.interval_sizes.iter().max().copied().unwrap() as f64
     }
 
     pub fn chunk_size_avg(&self) -> f64 {
@@ -143,14 +141,14 @@ impl AlgorithmResult {
     }
 
     pub fn min_chunk_size(&self) -> f64 {
-        self.chunks.values().min().map(|v| *v).unwrap() as f64
+        self.chunks.values().min().copied().unwrap() as f64
     }
 
     pub fn max_chunk_size(&self) -> f64 {
-        self.chunks.values().max().map(|v| *v).unwrap() as f64
+        self.chunks.values().max().copied().unwrap() as f64
     }
 
     pub fn result_chunk_sizes(&self) -> Vec<usize> {
-        self.chunks.values().map(|v| *v).collect()
+        self.chunks.values().copied().collect()
     }
 }
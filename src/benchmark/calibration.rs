@@ -0,0 +1,192 @@
+use cdc_comparison::chunkers::SplitProbability;
+use cdc_comparison::{ChunkSizes, Chunker, SliceChunks};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
+
+use crate::benchmark::{AvgSizeToSizes, NamedChunker};
+
+/// The split points of a chunker on random input.
+pub struct Calibration {
+    /// The probability of the main masks. The backup and the regression masks cut only at the max size,
+    /// so they are not part of it.
+    pub expected: Option<SplitProbability>,
+    /// The share of the bytes that were split points among the bytes that could be ones, before the center.
+    pub before_center: f64,
+    pub after_center: f64,
+    /// The chunks that are cut where the chunker reached the max size: at the max size,
+    /// or earlier at a backup or a regression point.
+    pub max_size_cuts: usize,
+    pub chunk_count: usize,
+    pub avg_size: f64,
+}
+
+/// Measures the boundary rate of the chunker on the data.
+///
+/// The chunker checks the bytes from the min size on, and a chunk of length `L` means that the bytes before `L`
+/// weren't split points, while the byte at `L` was. So every chunk adds the checked bytes to the side of the center
+/// that they are on, and a split point to the side of `L`. The chunks of the max size have no split point.
+/// The last chunk is left out, because it ends with the input.
+///
+/// A chunk that is shorter than the max size is cut by a mask when the chunker cuts it at the same length
+/// in a buffer that ends with the byte after it. Otherwise the chunker cut it only after it reached the max size,
+/// like at a backup or a regression point, so the bytes up to the max size had no split point.
+pub fn calibrate(chunker: &dyn Chunker, chunk_sizes: ChunkSizes, data: &[u8]) -> Calibration {
+    let expected = chunker.split_probability(&chunk_sizes);
+    let (min, max) = (chunk_sizes.min_size(), chunk_sizes.max_size());
+    // The chunkers that don't report the probability are measured around the avg size.
+    let center = expected.map_or(chunk_sizes.avg_size(), |expected| expected.center).clamp(min, max);
+    let chunks = SliceChunks::new(data, chunker, chunk_sizes).collect::<Vec<_>>();
+    let chunks = &chunks[..chunks.len().saturating_sub(1)];
+
+    let (mut checked_before, mut splits_before, mut checked_after, mut splits_after) = (0, 0, 0, 0);
+    let mut max_size_cuts = 0;
+    for chunk in chunks {
+        let (offset, length) = (chunk.offset, chunk.length);
+        let is_split =
+            length < max && chunker.find_split_point(&data[offset..offset + length + 1], &chunk_sizes) == length;
+        if !is_split {
+            max_size_cuts += 1;
+        }
+        let last_checked = if is_split { length + 1 } else { max };
+        checked_before += last_checked.clamp(min, center) - min;
+        checked_after += last_checked.max(center) - center;
+        if is_split && length < center {
+            splits_before += 1;
+        } else if is_split {
+            splits_after += 1;
+        }
+    }
+    let rate = |splits: usize, checked: usize| if checked == 0 { f64::NAN } else { splits as f64 / checked as f64 };
+    Calibration {
+        expected,
+        before_center: rate(splits_before, checked_before),
+        after_center: rate(splits_after, checked_after),
+        max_size_cuts,
+        chunk_count: chunks.len(),
+        avg_size: chunks.iter().map(|chunk| chunk.length).sum::<usize>() as f64 / chunks.len() as f64,
+    }
+}
+
+/// Chunks random data with every chunker and prints the measured boundary rates next to the ones that
+/// the main masks of the chunker assume. The chunkers whose average chunk size differs from the avg size
+/// by more than the tolerance are flagged.
+pub fn print_calibration(
+    avg_sizes: Vec<usize>,
+    avg_size_to_chunk_sizes: AvgSizeToSizes,
+    chunkers_with_names: Vec<NamedChunker>,
+    bytes: usize,
+    tolerance: f64,
+) {
+    let mut data = vec![0u8; bytes];
+    ChaCha20Rng::seed_from_u64(23).fill(data.as_mut_slice());
+    let runs = chunkers_with_names
+        .into_iter()
+        .flat_map(|chunker| {
+            let chunk_sizes = avg_sizes.iter().flat_map(|avg_size| avg_size_to_chunk_sizes(*avg_size));
+            std::iter::repeat(chunker).zip(chunk_sizes)
+        })
+        .collect::<Vec<_>>();
+    let calibrations = runs
+        .par_iter()
+        .map(|((name, chunker_builder), chunk_sizes)| match chunker_builder(*chunk_sizes) {
+            Ok(chunker) => Some(calibrate(chunker.as_ref(), *chunk_sizes, &data)),
+            Err(e) => {
+                eprintln!("Skipping {} {}: {}", name, chunk_sizes, e);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let format_rate = |rate: f64| match rate {
+        rate if rate.is_nan() => "-".to_string(),
+        0.0 => "0".to_string(),
+        rate => format!("1/{:.0}", 1.0 / rate),
+    };
+    println!(
+        "| Chunker | Sizes | Chunks | Avg size | Drift | Max size cuts | Before center | Expected | After center | Expected | |"
+    );
+    println!("|---|---|---|---|---|---|---|---|---|---|---|");
+    for (((name, _), chunk_sizes), calibration) in runs.iter().zip(calibrations) {
+        let Some(calibration) = calibration else {
+            continue;
+        };
+        let drift = calibration.avg_size / chunk_sizes.avg_size() as f64 - 1.0;
+        let (expected_before, expected_after) = match calibration.expected {
+            Some(expected) => (format_rate(expected.before_center), format_rate(expected.after_center)),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        println!(
+            "| {} | {} | {} | {:.0} | {:+.1}% | {} | {} | {} | {} | {} | {} |",
+            name,
+            chunk_sizes,
+            calibration.chunk_count,
+            calibration.avg_size,
+            drift * 100.0,
+            calibration.max_size_cuts,
+            format_rate(calibration.before_center),
+            expected_before,
+            format_rate(calibration.after_center),
+            expected_after,
+            if drift.abs() > tolerance { "drift" } else { "" }
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdc_comparison::util::{KB, MB};
+    use cdc_comparison::{build_chunker, ChunkSizes};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::benchmark::calibration::calibrate;
+
+    #[test]
+    fn should_measure_the_rates_of_the_masks() {
+        let mut data = vec![0u8; 16 * MB];
+        ChaCha20Rng::seed_from_u64(23).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 8 * KB, 64 * KB);
+        for spec in ["buzhash:width=64,window=64,nc=1", "gear:width=64,nc=2", "casync"] {
+            let chunker = build_chunker(spec, sizes).unwrap();
+            let calibration = calibrate(chunker.as_ref(), sizes, &data);
+            let expected = calibration.expected.unwrap();
+            assert!(calibration.chunk_count > 500, "{} {}", spec, calibration.chunk_count);
+            let before = calibration.before_center / expected.before_center;
+            let after = calibration.after_center / expected.after_center;
+            assert!((before - 1.0).abs() < 0.15, "{} {}", spec, before);
+            assert!((after - 1.0).abs() < 0.15, "{} {}", spec, after);
+        }
+    }
+
+    #[test]
+    fn should_leave_the_backup_and_regression_cuts_out_of_the_rates() {
+        let mut data = vec![0u8; 64 * MB];
+        ChaCha20Rng::seed_from_u64(23).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(32 * KB, 64 * KB, 128 * KB);
+        for spec in ["tttd:hash=buzhash,window=64", "gear:width=64,rc=4"] {
+            let chunker = build_chunker(spec, sizes).unwrap();
+            let calibration = calibrate(chunker.as_ref(), sizes, &data);
+            let expected = calibration.expected.unwrap();
+            // The chunks that find no split point of the main mask between the min and the max sizes.
+            let max_size_share = calibration.max_size_cuts as f64 / calibration.chunk_count as f64;
+            let expected_share = (-expected.before_center * (sizes.max_size() - sizes.min_size()) as f64).exp();
+            assert!((max_size_share / expected_share - 1.0).abs() < 0.15, "{} {}", spec, max_size_share);
+            let before = calibration.before_center / expected.before_center;
+            assert!((before - 1.0).abs() < 0.15, "{} {}", spec, before);
+        }
+    }
+
+    #[test]
+    fn should_find_the_pci_rate_below_the_binomial_one() {
+        let mut data = vec![0u8; 16 * MB];
+        ChaCha20Rng::seed_from_u64(23).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 8 * KB, 64 * KB);
+        let chunker = build_chunker("pci:window=5", sizes).unwrap();
+        let calibration = calibrate(chunker.as_ref(), sizes, &data);
+        // The windows of the neighbouring bytes overlap, so a byte after a byte that isn't a split point
+        // is less likely to be one than the independent windows of the binomial threshold assume.
+        let before = calibration.before_center / calibration.expected.unwrap().before_center;
+        assert!(before < 0.9, "{}", before);
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cdc_comparison::util::mapped_files::MappedFiles;
+use cdc_comparison::util::multi_file_dir::MultiFileRead;
+use cdc_comparison::util::size_to_str_f64;
+use cdc_comparison::{ChunkSizes, ChunkStream, Chunker, HashedBoundary, SliceChunks};
+
+use crate::benchmark::{GetFilesInDirectoryFunction, InputMode};
+
+/// An input of the evaluation. The loaded and the mapped inputs are shared by all the runs that chunk them.
+#[derive(Clone)]
+pub enum Input {
+    /// Every run reads the files from the disk.
+    Streamed(Vec<PathBuf>),
+    /// The bytes of all the files, loaded once.
+    Loaded(Arc<[u8]>),
+    /// The files mapped into memory once.
+    Mapped(Arc<MappedFiles>),
+}
+
+impl Input {
+    /// Chunks the input without file boundaries and passes every chunk to `append_chunk`.
+    pub fn chunk(
+        &self,
+        chunker: &dyn Chunker,
+        chunk_sizes: ChunkSizes,
+        mut append_chunk: impl FnMut(HashedBoundary),
+    ) -> std::io::Result<()> {
+        match self {
+            Input::Streamed(files) => {
+                let source = MultiFileRead::new(files.clone())?;
+                for chunk in ChunkStream::new(source, chunker, chunk_sizes).into_hashed_boundaries() {
+                    append_chunk(chunk?);
+                }
+            }
+            Input::Loaded(data) => {
+                SliceChunks::new(data, chunker, chunk_sizes).for_each(|chunk| append_chunk(chunk.into()))
+            }
+            Input::Mapped(files) => {
+                let mut chunks = files.chunks(chunker, chunk_sizes);
+                while let Some(chunk) = chunks.next_chunk() {
+                    append_chunk(chunk.into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Opens every input of the evaluation matrix once, so that the runs chunk from memory and not from the disk.
+///
+/// In the [InputMode::Memory] mode the inputs are loaded while they fit into the memory budget together.
+/// The inputs that don't fit are streamed from the disk by every run instead.
+pub struct DatasetCache {
+    input_mode: InputMode,
+    memory_budget: usize,
+    /// The bytes loaded so far.
+    loaded_size: usize,
+    inputs: HashMap<PathBuf, Input>,
+}
+
+impl DatasetCache {
+    pub fn new(input_mode: InputMode, memory_budget: usize) -> Self {
+        DatasetCache { input_mode, memory_budget, loaded_size: 0, inputs: HashMap::new() }
+    }
+
+    /// Returns the input of the file or directory, which is opened on the first request only.
+    pub fn get(&mut self, input: PathBuf, get_files: GetFilesInDirectoryFunction) -> std::io::Result<Input> {
+        if let Some(cached) = self.inputs.get(&input) {
+            return Ok(cached.clone());
+        }
+        let files = get_files(input.clone());
+        let cached = match self.input_mode {
+            InputMode::Read => Input::Streamed(files),
+            InputMode::Mmap => Input::Mapped(Arc::new(MappedFiles::open(files)?)),
+            InputMode::Memory => {
+                let size = files
+                    .iter()
+                    .map(fs::metadata)
+                    .map(|metadata| Ok(metadata?.len() as usize))
+                    .sum::<std::io::Result<usize>>()?;
+                if self.loaded_size + size <= self.memory_budget {
+                    self.loaded_size += size;
+                    Input::Loaded(load_files(&files, size)?)
+                } else {
+                    eprintln!(
+                        "Streaming {:?}: {} don't fit into the memory budget of {}, and {} are loaded already",
+                        input,
+                        size_to_str_f64(size as f64),
+                        size_to_str_f64(self.memory_budget as f64),
+                        size_to_str_f64(self.loaded_size as f64)
+                    );
+                    Input::Streamed(files)
+                }
+            }
+        };
+        self.inputs.insert(input, cached.clone());
+        Ok(cached)
+    }
+}
+
+fn load_files(files: &[PathBuf], size: usize) -> std::io::Result<Arc<[u8]>> {
+    let mut data = Vec::with_capacity(size);
+    for path in files {
+        File::open(path)?.read_to_end(&mut data)?;
+    }
+    Ok(data.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use cdc_comparison::util::read_files_in_dir_sorted_by_name;
+    use cdc_comparison::{build_chunker, ChunkSizes, HashedBoundary};
+
+    use crate::benchmark::dataset_cache::{DatasetCache, Input};
+    use crate::benchmark::InputMode;
+
+    #[test]
+    fn should_stream_inputs_over_the_memory_budget() {
+        let dir = std::env::temp_dir().join(format!("dataset-cache-{}", std::process::id()));
+        let (first, second) = (dir.join("first"), dir.join("second"));
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+        let bytes = (0..300_000u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect::<Vec<_>>();
+        fs::write(first.join("a"), &bytes[..100_000]).unwrap();
+        fs::write(first.join("b"), &bytes[100_000..]).unwrap();
+        fs::write(second.join("a"), &bytes).unwrap();
+
+        let mut cache = DatasetCache::new(InputMode::Memory, 400_000);
+        let first_input = cache.get(first.clone(), read_files_in_dir_sorted_by_name).unwrap();
+        let second_input = cache.get(second.clone(), read_files_in_dir_sorted_by_name).unwrap();
+        assert!(matches!(&first_input, Input::Loaded(data) if data.len() == 300_000));
+        assert!(matches!(&second_input, Input::Streamed(files) if files.len() == 1));
+        let cached_input = cache.get(first.clone(), read_files_in_dir_sorted_by_name).unwrap();
+        assert!(matches!((&first_input, &cached_input), (Input::Loaded(a), Input::Loaded(b)) if Arc::ptr_eq(a, b)));
+
+        let sizes = ChunkSizes::new(4096, 8192, 16384);
+        let chunker = build_chunker("gear:width=64", sizes).unwrap();
+        let chunk = |input: &Input| {
+            let mut chunks: Vec<HashedBoundary> = Vec::new();
+            input.chunk(chunker.as_ref(), sizes, |chunk| chunks.push(chunk)).unwrap();
+            chunks
+        };
+        let mapped_input = DatasetCache::new(InputMode::Mmap, 0).get(first, read_files_in_dir_sorted_by_name).unwrap();
+        assert_eq!(chunk(&first_input), chunk(&second_input));
+        assert_eq!(chunk(&first_input), chunk(&mapped_input));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use cdc_comparison::chunkers::registry::{expand_specs, ChunkerSpec, FAMILIES};
+use cdc_comparison::util::parse_size;
+use itertools::Itertools;
+use serde::{Deserialize, Deserializer};
+
+use crate::benchmark::{named_chunker, InputMode, NamedChunker, SizePreset};
+
+/// The experiment that reproduces the published results on the postgres binaries.
+const POSTGRES_EXPERIMENT: &str = include_str!("../../experiments/postgres.toml");
+
+/// Describes the datasets, the chunk sizes and the grids of chunker parameters that are evaluated together.
+/// The experiment is read from a TOML file, or from a JSON file when the file has the `.json` extension.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Experiment {
+    /// Directory for the reports. Every dataset gets its own subdirectory.
+    #[serde(default = "default_output")]
+    pub output: PathBuf,
+    #[serde(deserialize_with = "deserialize_sizes")]
+    pub avg_sizes: Vec<usize>,
+    #[serde(default = "default_preset")]
+    pub preset: SizePreset,
+    /// The way the inputs are read, `memory` by default.
+    #[serde(default = "default_input_mode")]
+    pub input_mode: InputMode,
+    #[serde(default)]
+    pub datasets: Vec<Dataset>,
+    #[serde(default)]
+    pub chunkers: Vec<ChunkerGrid>,
+    /// Single chunkers as spec strings, e.g. `fastcdc2020:nc=3`. They are evaluated after the grids.
+    #[serde(default)]
+    pub specs: Vec<ChunkerSpec>,
+}
+
+/// Inputs whose chunks are deduplicated against each other.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dataset {
+    pub name: String,
+    /// Files or directories. The files of a directory are read in the name order without file boundaries.
+    pub inputs: Vec<PathBuf>,
+}
+
+/// A parameter dimension of a grid. Either a single value or a list of values.
+#[derive(Clone, Debug)]
+pub struct Grid<T>(Vec<T>);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Grid<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value<T> {
+            One(T),
+            Many(Vec<T>),
+        }
+        match Value::deserialize(deserializer)? {
+            Value::One(value) => Ok(Grid(vec![value])),
+            Value::Many(values) => Ok(Grid(values)),
+        }
+    }
+}
+
+/// An algorithm family with the parameter values to evaluate, e.g. `family = "buzhash"` with `width = [32, 64]`.
+/// Every combination of the values becomes a separate chunker.
+/// The parameters and their defaults are the ones of the spec strings, see [cdc_comparison::chunkers::registry],
+/// so the grid is expanded by [expand_specs].
+pub struct ChunkerGrid(Vec<ChunkerSpec>);
+
+impl<'de> Deserialize<'de> for ChunkerGrid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Number(u64),
+            Text(String),
+        }
+        #[derive(Deserialize)]
+        struct Table {
+            family: String,
+            #[serde(flatten)]
+            params: BTreeMap<String, Grid<Value>>,
+        }
+        let table = Table::deserialize(deserializer)?;
+        // The parameters are taken in the order of the family, so that the last one in [FAMILIES] changes the fastest.
+        let order = FAMILIES.iter().find(|(family, _)| *family == table.family).map_or("", |(_, params)| params);
+        let position = |key: &str| order.split(',').position(|param| param.split('=').next() == Some(key));
+        let params = table
+            .params
+            .iter()
+            .sorted_by_key(|(key, _)| position(key).unwrap_or(usize::MAX))
+            .map(|(key, values)| {
+                let values = values.0.iter().map(|value| match value {
+                    Value::Number(number) => number.to_string(),
+                    Value::Text(text) => text.clone(),
+                });
+                format!("{}={}", key, values.format("|"))
+            })
+            .join(",");
+        expand_specs(&format!("{}:{}", table.family, params)).map(ChunkerGrid).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Experiment {
+    pub fn read(path: &Path) -> std::io::Result<Experiment> {
+        let content = fs::read_to_string(path)?;
+        if path.extension().is_some_and(|extension| extension == "json") {
+            serde_json::from_str(&content).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+        } else {
+            Self::from_toml(&content)
+        }
+    }
+
+    pub fn from_toml(content: &str) -> std::io::Result<Experiment> {
+        toml::from_str(content).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// The chunkers of all grids and specs. Chunkers that appear several times are evaluated once.
+    pub fn chunker_specs(&self) -> Vec<ChunkerSpec> {
+        self.chunkers
+            .iter()
+            .flat_map(|grid| grid.0.iter().cloned())
+            .chain(self.specs.iter().cloned())
+            .unique_by(ChunkerSpec::to_string)
+            .collect()
+    }
+
+    pub fn chunkers(&self) -> Vec<NamedChunker> {
+        self.chunker_specs().into_iter().map(named_chunker).collect()
+    }
+}
+
+/// The experiment on the postgres binaries.
+pub fn default_experiment() -> Experiment {
+    Experiment::from_toml(POSTGRES_EXPERIMENT).expect("The postgres experiment is valid")
+}
+
+/// The chunkers of the experiment on the postgres binaries.
+pub fn default_chunkers() -> Vec<NamedChunker> {
+    default_experiment().chunkers()
+}
+
+fn deserialize_sizes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<usize>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(usize),
+        Text(String),
+    }
+    Vec::<Size>::deserialize(deserializer)?
+        .into_iter()
+        .map(|size| match size {
+            Size::Bytes(bytes) => Ok(bytes),
+            Size::Text(text) => parse_size(&text).map_err(serde::de::Error::custom),
+        })
+        .collect()
+}
+
+fn default_output() -> PathBuf {
+    PathBuf::from("results/json")
+}
+
+fn default_preset() -> SizePreset {
+    SizePreset::Standard
+}
+
+fn default_input_mode() -> InputMode {
+    InputMode::Memory
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::benchmark::experiment::Experiment;
+
+    #[test]
+    fn should_derive_names_from_parameters() {
+        let experiment = Experiment::from_toml(
+            r#"
+            avg_sizes = ["64KB"]
+            specs = ["restic:pol_seed=7", "buzhash_reg:width=32,window=64"]
+
+            [[chunkers]]
+            family = "buzhash_reg"
+            width = 32
+            window = [64, 256]
+
+            [[chunkers]]
+            family = "buzhash"
+            width = 128
+            table = "buz"
+            window = 256
+            nc = [0, 2]
+            mask = "spread"
+
+            [[chunkers]]
+            family = "adler32"
+            window = [64, 4096]
+            nc = 1
+            "#,
+        )
+        .unwrap();
+        let names = experiment.chunkers().into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "Buzhash32Reg 64",
+                "Buzhash32Reg 256",
+                "Buzhash128b 256 spread",
+                "Buzhash128b 256 spread nc2",
+                "Adler32 64 nc1",
+                "Adler32 4096 nc1",
+                "Restic seed7"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_grids() {
+        let error = Experiment::from_toml(
+            r#"
+            avg_sizes = ["64KB"]
+
+            [[chunkers]]
+            family = "gear"
+            width = [64, 16]
+            "#,
+        )
+        .err()
+        .unwrap();
+        assert!(error.to_string().contains("the width 16 is not one of [32, 64, 128]"), "{}", error);
+    }
+}
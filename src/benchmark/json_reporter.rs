@@ -6,11 +6,11 @@ use std::fs::File;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
+use cdc_comparison::util::{read_files_in_dir_sorted_by_name, size_to_str, size_to_str_f64};
+use cdc_comparison::ChunkSizes;
 use serde::{Deserialize, Serialize};
 
 use crate::benchmark::benchmark_result::AlgorithmResult;
-use crate::util::chunk_sizes::ChunkSizes;
-use crate::util::{read_files_in_dir_sorted_by_name, size_to_str, size_to_str_f64};
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -40,7 +40,7 @@ struct MergedReport {
 }
 
 pub fn prepare_json_dir(output_dir: &Path) -> std::io::Result<()> {
-    fs::create_dir_all(&output_dir.join("runs"))
+    fs::create_dir_all(output_dir.join("runs"))
 }
 
 fn read_single_report(path: PathBuf) -> std::io::Result<Result> {
@@ -51,7 +51,7 @@ fn read_single_report(path: PathBuf) -> std::io::Result<Result> {
 }
 
 pub fn merge_results_dir(output_dir: &Path) -> std::io::Result<()> {
-    let report_paths = read_files_in_dir_sorted_by_name(&output_dir.join("runs"));
+    let report_paths = read_files_in_dir_sorted_by_name(output_dir.join("runs"));
     let results = report_paths.into_iter().map(read_single_report).collect::<std::io::Result<Vec<Result>>>()?;
     merge_buz(output_dir, results.clone())?;
     merge_all(output_dir, results.clone())?;
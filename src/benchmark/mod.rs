@@ -1,23 +1,37 @@
 use std::collections::HashMap;
 use std::fs;
-use std::io::{BufReader, Write};
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 
+use cdc_comparison::hashes::table_analysis::{analyze_tables, AnalysisSettings};
+use cdc_comparison::util::multi_file_dir::MultiFileRead;
+use cdc_comparison::util::{read_files_in_dir_sorted_by_name, sha256_file, MB};
+use cdc_comparison::{ChunkSizes, ChunkStream, Chunker, ChunkerSpec, SpecError};
+use clap::ValueEnum;
 use rayon::prelude::*;
+use regex::Regex;
+use serde::Deserialize;
 
 use crate::benchmark::benchmark_result::AlgorithmResult;
-use crate::benchmark::json_reporter::{merge_results_dir, prepare_json_dir, write_result_json};
-use crate::chunkers::Chunker;
-use crate::util::chunk_sizes::ChunkSizes;
-use crate::util::chunk_stream::ChunkStream;
-use crate::util::multi_file_dir::MultiFileRead;
-use crate::util::{read_files_in_dir_sorted_by_name, sha256_file, MB};
+use crate::benchmark::dataset_cache::{DatasetCache, Input};
+use crate::benchmark::experiment::{default_chunkers, Experiment};
+use crate::benchmark::json_reporter::{prepare_json_dir, write_result_json};
+use crate::cli::FilterArgs;
 
 mod benchmark_result;
+pub mod calibration;
+pub mod dataset_cache;
+pub mod experiment;
 mod json_reporter;
+pub mod resilience;
+pub mod synthetic;
+
+pub use json_reporter::merge_results_dir;
 
 pub type ChunkerName = String;
-pub type ChunkerBuilder = fn(ChunkSizes) -> Box<dyn Chunker>;
+pub type ChunkerBuilder = Arc<dyn Fn(ChunkSizes) -> Result<Box<dyn Chunker>, SpecError> + Send + Sync>;
 pub type NamedChunker = (ChunkerName, ChunkerBuilder);
 pub type GetFilesInDirectoryFunction = fn(PathBuf) -> Vec<PathBuf>;
 pub type AvgSizeToSizes = fn(usize) -> Vec<ChunkSizes>;
@@ -36,21 +50,126 @@ pub fn avg_to_standard_sizes(avg_size: usize) -> Vec<ChunkSizes> {
     ]
 }
 
+/// The `avg/2;avg;2*avg` sizes, which are the usual default for the chunkers with regression.
+pub fn avg_to_double_sizes(avg_size: usize) -> Vec<ChunkSizes> {
+    vec![ChunkSizes::new(avg_size / 2, avg_size, 2 * avg_size)]
+}
+
+/// The sizes recommended by ronomon https://github.com/ronomon/deduplication/issues/8#issue-810116157
+pub fn avg_to_ronomon_sizes(avg_size: usize) -> Vec<ChunkSizes> {
+    vec![ChunkSizes::new(avg_size / 2, avg_size, 3 * avg_size), ChunkSizes::new(avg_size / 2, avg_size, 4 * avg_size)]
+}
+
+/// The sizes used by casync https://github.com/systemd/casync/blob/main/src/cachunker.h#L16-L20
+pub fn avg_to_casync_sizes(avg_size: usize) -> Vec<ChunkSizes> {
+    vec![ChunkSizes::new(avg_size / 4, avg_size, 4 * avg_size)]
+}
+
+/// The sizes used by restic https://github.com/restic/chunker/blob/master/chunker.go#L15-L18
+pub fn avg_to_restic_sizes(avg_size: usize) -> Vec<ChunkSizes> {
+    vec![ChunkSizes::new(avg_size / 2, avg_size, 8 * avg_size)]
+}
+
+/// The sizes recommended for RC4 https://github.com/dbaarda/rollsum-chunking/blob/master/RESULTS.rst#summary
+pub fn avg_to_rc4_sizes(avg_size: usize) -> Vec<ChunkSizes> {
+    vec![
+        ChunkSizes::new(avg_size / 2, avg_size, (1.25 * avg_size as f64) as usize),
+        ChunkSizes::new(avg_size / 2, avg_size, (1.5 * avg_size as f64) as usize),
+        ChunkSizes::new(avg_size / 2, avg_size, (1.75 * avg_size as f64) as usize),
+    ]
+}
+
+#[derive(Copy, Clone, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SizePreset {
+    /// All the presets below together with `avg/2;avg;5*avg`.
+    Standard,
+    /// `avg/2;avg;2*avg`.
+    Double,
+    /// `avg/2;avg;3*avg` and `avg/2;avg;4*avg`.
+    Ronomon,
+    /// `avg/4;avg;4*avg`.
+    Casync,
+    /// `avg/2;avg;8*avg`.
+    Restic,
+    /// `avg/2;avg;1.25*avg`, `avg/2;avg;1.5*avg` and `avg/2;avg;1.75*avg`.
+    Rc4,
+}
+
+impl SizePreset {
+    pub fn avg_to_sizes(&self) -> AvgSizeToSizes {
+        match self {
+            SizePreset::Standard => avg_to_standard_sizes,
+            SizePreset::Double => avg_to_double_sizes,
+            SizePreset::Ronomon => avg_to_ronomon_sizes,
+            SizePreset::Casync => avg_to_casync_sizes,
+            SizePreset::Restic => avg_to_restic_sizes,
+            SizePreset::Rc4 => avg_to_rc4_sizes,
+        }
+    }
+}
+
+/// The way the runs of an evaluation read the inputs.
+#[derive(Copy, Clone, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputMode {
+    /// Every run reads the files from the disk into the buffer of its chunk stream.
+    Read,
+    /// The files are mapped into memory once, and every run chunks the mapped bytes without copying them.
+    Mmap,
+    /// The files are loaded into memory once while they fit into the memory budget, and are read otherwise.
+    Memory,
+}
+
+pub fn named_chunker(spec: ChunkerSpec) -> NamedChunker {
+    (spec.to_string(), Arc::new(move |sizes| spec.build(sizes)))
+}
+
+/// The chunkers of the specs, or of the experiment on the postgres binaries when there are no specs,
+/// that the filter selects.
+pub fn selected_chunkers(specs: Vec<ChunkerSpec>, filter: &FilterArgs) -> Vec<NamedChunker> {
+    let chunkers = if specs.is_empty() { default_chunkers() } else { specs.into_iter().map(named_chunker).collect() };
+    filter_chunkers(chunkers, &filter.filter, &filter.exclude)
+}
+
+/// Keeps the chunkers whose names match any of the `include` expressions and none of the `exclude` ones.
+/// An empty `include` list matches all chunkers.
+pub fn filter_chunkers(chunkers: Vec<NamedChunker>, include: &[Regex], exclude: &[Regex]) -> Vec<NamedChunker> {
+    chunkers.into_iter().filter(|(name, _)| is_selected(name, include, exclude)).collect()
+}
+
+pub fn is_selected(name: &str, include: &[Regex], exclude: &[Regex]) -> bool {
+    (include.is_empty() || include.iter().any(|regex| regex.is_match(name)))
+        && !exclude.iter().any(|regex| regex.is_match(name))
+}
+
 pub fn evaluate(
     avg_sizes: Vec<usize>,
     avg_size_to_chunk_sizes: AvgSizeToSizes,
     chunkers_with_names: Vec<NamedChunker>,
     get_files: GetFilesInDirectoryFunction,
     input_dirs: Vec<PathBuf>,
+    dataset_cache: &mut DatasetCache,
     output_dir: &Path,
 ) -> std::io::Result<()> {
     prepare_json_dir(output_dir)?;
+    let inputs = input_dirs
+        .into_iter()
+        .map(|input_dir| dataset_cache.get(input_dir, get_files))
+        .collect::<std::io::Result<Vec<Input>>>()?;
     let chunk_sizes_and_chunkers = chunkers_with_names.into_iter().flat_map(|chunker| {
         let chunk_sizes = avg_sizes.iter().flat_map(|avg_size| avg_size_to_chunk_sizes(*avg_size));
         std::iter::repeat(chunker).zip(chunk_sizes)
     });
-    chunk_sizes_and_chunkers.par_bridge().try_for_each(|(chunker, chunk_sizes)| {
-        let result = run_without_file_boundaries(input_dirs.clone(), chunk_sizes, &chunker, &get_files)?;
+    chunk_sizes_and_chunkers.par_bridge().try_for_each(|((name, chunker_builder), chunk_sizes)| {
+        let chunker = match chunker_builder(chunk_sizes) {
+            Ok(chunker) => chunker,
+            Err(e) => {
+                eprintln!("Skipping {} {}: {}", name, chunk_sizes, e);
+                return Ok(());
+            }
+        };
+        let result = run_without_file_boundaries(&inputs, chunk_sizes, &name, chunker.as_ref())?;
         write_result_json(output_dir, &result)?;
         Ok::<(), std::io::Error>(())
     })?;
@@ -58,33 +177,126 @@ pub fn evaluate(
     Ok(())
 }
 
+/// Evaluates the chunkers of the experiment on every dataset.
+/// The inputs that are shared by several datasets are loaded once.
+pub fn evaluate_experiment(
+    experiment: Experiment,
+    memory_budget: usize,
+    include: &[Regex],
+    exclude: &[Regex],
+) -> std::io::Result<()> {
+    let chunkers = filter_chunkers(experiment.chunkers(), include, exclude);
+    let mut dataset_cache = DatasetCache::new(experiment.input_mode, memory_budget);
+    for dataset in experiment.datasets {
+        evaluate(
+            experiment.avg_sizes.clone(),
+            experiment.preset.avg_to_sizes(),
+            chunkers.clone(),
+            read_files_in_dir_sorted_by_name,
+            dataset.inputs,
+            &mut dataset_cache,
+            &experiment.output.join(dataset.name),
+        )?;
+    }
+    Ok(())
+}
+
 fn run_without_file_boundaries(
-    input_dirs: Vec<PathBuf>,
+    inputs: &[Input],
     chunk_sizes: ChunkSizes,
-    named_chunker: &NamedChunker,
-    get_files: &GetFilesInDirectoryFunction,
+    name: &str,
+    chunker: &dyn Chunker,
 ) -> std::io::Result<AlgorithmResult> {
-    let (name, chunker_builder) = named_chunker;
     eprintln!("{} {}", name, chunk_sizes);
-    let chunker = chunker_builder(chunk_sizes);
-    let mut cdc_result = AlgorithmResult::new(name.clone(), chunk_sizes);
-    let mut process_directory = |dir: PathBuf| -> std::io::Result<()> {
-        let source = BufReader::with_capacity(16 * MB, MultiFileRead::new(get_files(dir))?);
-        for result in ChunkStream::new(source, &chunker, chunk_sizes) {
-            let chunk = result?;
-            cdc_result.append_chunk(chunk);
-        }
+    let mut cdc_result = AlgorithmResult::new(name.to_string(), chunk_sizes);
+    for input in inputs {
+        input.chunk(chunker, chunk_sizes, |chunk| cdc_result.append_chunk(chunk))?;
         cdc_result.complete_input();
-        Ok(())
-    };
-    for dir in input_dirs {
-        process_directory(dir)?;
     }
     Ok(cdc_result)
 }
 
+/// The way the chunks are taken from the [ChunkStream].
+#[derive(Copy, Clone, ValueEnum)]
+pub enum StreamMode {
+    /// Slices of the stream buffer.
+    Borrowed,
+    /// A new vector per chunk.
+    Owned,
+}
+
+/// Chunks the inputs with every chunker and prints the throughput of the stream in every mode.
+/// The chunks are only counted, so that the time is spent in the stream and in the chunker.
+pub fn measure_throughput(
+    avg_sizes: Vec<usize>,
+    avg_size_to_chunk_sizes: AvgSizeToSizes,
+    chunkers_with_names: Vec<NamedChunker>,
+    modes: Vec<StreamMode>,
+    get_files: GetFilesInDirectoryFunction,
+    input_dirs: Vec<PathBuf>,
+) -> std::io::Result<()> {
+    println!("| Chunker | Sizes | Mode | Chunks | MB/s |");
+    println!("|---|---|---|---|---|");
+    for (name, chunker_builder) in chunkers_with_names {
+        for chunk_sizes in avg_sizes.iter().flat_map(|avg_size| avg_size_to_chunk_sizes(*avg_size)) {
+            for mode in &modes {
+                // Every mode gets a new chunker, so that the stateful chunkers don't learn from the previous mode.
+                let chunker = match chunker_builder(chunk_sizes) {
+                    Ok(chunker) => chunker,
+                    Err(e) => {
+                        eprintln!("Skipping {} {}: {}", name, chunk_sizes, e);
+                        break;
+                    }
+                };
+                let start = Instant::now();
+                let (mut total_size, mut chunk_count) = (0, 0);
+                for dir in &input_dirs {
+                    let source = MultiFileRead::new(get_files(dir.clone()))?;
+                    let mut stream = ChunkStream::new(source, chunker.as_ref(), chunk_sizes);
+                    match mode {
+                        StreamMode::Borrowed => {
+                            while let Some(chunk) = stream.next_chunk() {
+                                total_size += chunk?.length;
+                                chunk_count += 1;
+                            }
+                        }
+                        StreamMode::Owned => {
+                            for chunk in stream.into_owned() {
+                                total_size += chunk?.data.len();
+                                chunk_count += 1;
+                            }
+                        }
+                    }
+                }
+                let throughput = total_size as f64 / MB as f64 / start.elapsed().as_secs_f64();
+                let mode = mode.to_possible_value().expect("No skipped modes");
+                println!("| {} | {} | {} | {} | {:.1} |", name, chunk_sizes, mode.get_name(), chunk_count, throughput);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prints the quality of every lookup table, see [analyze_tables].
+pub fn print_table_analysis(settings: AnalysisSettings) {
+    println!("| Table | Hash | Bit imbalance | Bit correlation | Avalanche | Avalanche bias | Split ratio |");
+    println!("|---|---|---|---|---|---|---|");
+    for report in analyze_tables(settings) {
+        println!(
+            "| {} | {} | {} | {:.3} | {:.3} | {:.3} | {:.3} |",
+            report.table,
+            report.hash,
+            report.max_bit_imbalance,
+            report.max_bit_correlation,
+            report.avalanche,
+            report.max_avalanche_bias,
+            report.split_ratio
+        );
+    }
+}
+
 pub fn evaluate_full_files(input_dirs: Vec<PathBuf>, output_dir: &Path) -> std::io::Result<()> {
-    fs::create_dir_all(&output_dir)?;
+    fs::create_dir_all(output_dir)?;
     let mut files: HashMap<String, u64> = HashMap::new();
     let mut total_size: u64 = 0;
     for file_path in input_dirs.iter().flat_map(read_files_in_dir_sorted_by_name) {
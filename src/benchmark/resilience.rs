@@ -0,0 +1,319 @@
+use cdc_comparison::{ChunkSizes, Chunker, SliceChunks};
+use clap::ValueEnum;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
+
+use crate::benchmark::synthetic::Generator;
+use crate::benchmark::{AvgSizeToSizes, NamedChunker};
+
+/// A single edit of the base input, which shifts all the bytes after it.
+#[derive(Copy, Clone, ValueEnum)]
+pub enum ShiftEdit {
+    Insert,
+    Delete,
+}
+
+/// The chunks of the edited input from the edit until a chunk ends where a chunk of the base input ends.
+pub struct Resync {
+    /// From the edit position to the first common boundary after the edit. `None` when the boundaries
+    /// don't resynchronize within the limit.
+    pub distance: Option<usize>,
+    /// The chunks of the edited input that are not chunks of the base input.
+    pub changed_chunks: usize,
+}
+
+/// The resynchronization of the chunks after an edit at a random position, for many positions.
+pub struct ResyncSummary {
+    pub samples: usize,
+    pub resynced: usize,
+    pub mean_distance: f64,
+    /// `None` when no edit resynchronizes.
+    pub p90_distance: Option<usize>,
+    pub mean_changed_chunks: f64,
+    pub max_changed_chunks: usize,
+}
+
+/// The settings of the edits that are applied to the base input.
+pub struct ShiftSettings {
+    pub edits: Vec<ShiftEdit>,
+    /// The lengths of the inserted or deleted bytes.
+    pub edit_sizes: Vec<usize>,
+    /// The number of the random edit positions for every chunker, edit and length.
+    pub samples: usize,
+    /// The distance is searched up to this number of max sizes after the edit.
+    pub limit_chunks: usize,
+    /// The seed of the edit positions. Every chunker gets the same positions.
+    pub seed: u64,
+}
+
+/// The stream of the ChaCha generator for the edit positions, so that the positions don't repeat
+/// the random bytes of a generator with the same seed.
+const POSITIONS_STREAM: u64 = 1;
+
+/// The base input with the boundaries that a chunker finds in it.
+pub struct ChunkedBase<'a> {
+    /// Builds a new chunker for the base input and for every edited input, so that the stateful chunkers
+    /// don't remember the base input, and the results don't depend on the order of the edits.
+    new_chunker: &'a dyn Fn() -> Box<dyn Chunker>,
+    chunk_sizes: ChunkSizes,
+    data: &'a [u8],
+    /// The end offsets of the chunks.
+    boundaries: Vec<usize>,
+    /// Only this number of bytes after an edit is chunked.
+    limit: usize,
+}
+
+impl<'a> ChunkedBase<'a> {
+    pub fn new(
+        new_chunker: &'a dyn Fn() -> Box<dyn Chunker>,
+        chunk_sizes: ChunkSizes,
+        data: &'a [u8],
+        limit: usize,
+    ) -> Self {
+        let chunker = new_chunker();
+        let boundaries =
+            SliceChunks::new(data, chunker.as_ref(), chunk_sizes).map(|chunk| chunk.offset + chunk.length).collect();
+        Self { new_chunker, chunk_sizes, data, boundaries, limit }
+    }
+
+    /// Applies the edit of the bytes at the position and chunks the edited input from the start of the chunk
+    /// of the base input that contains the position. The chunks before it don't depend on the edit.
+    ///
+    /// The boundaries are compared in the offsets of the base input: the offsets after an insertion are moved
+    /// back by the inserted length, and the offsets after a deletion are moved forward by the deleted length.
+    /// The end of the input is always a common boundary.
+    pub fn measure_resync(&self, edit: ShiftEdit, position: usize, bytes: &[u8]) -> Resync {
+        let boundaries = &self.boundaries;
+        let start = match boundaries.binary_search(&position) {
+            Ok(index) => boundaries[index],
+            Err(0) => 0,
+            Err(index) => boundaries[index - 1],
+        };
+        let (after_edit, edited_length) = match edit {
+            ShiftEdit::Insert => (position + bytes.len(), self.data.len() + bytes.len()),
+            ShiftEdit::Delete => (position, self.data.len() - bytes.len()),
+        };
+        let to_base = |offset: usize| match edit {
+            ShiftEdit::Insert => offset - bytes.len(),
+            ShiftEdit::Delete => offset + bytes.len(),
+        };
+        let base_tail = &self.data[to_base(after_edit)..];
+        let mut window = self.data[start..position].to_vec();
+        if let ShiftEdit::Insert = edit {
+            window.extend_from_slice(bytes);
+        }
+        window.extend_from_slice(&base_tail[..self.limit.min(base_tail.len())]);
+        let window_end = start + window.len();
+
+        let chunker = (self.new_chunker)();
+        let mut changed_chunks = 0;
+        for chunk in SliceChunks::new(&window, chunker.as_ref(), self.chunk_sizes) {
+            let end = start + chunk.offset + chunk.length;
+            // The last chunk of a window that is cut by the limit doesn't end with a boundary.
+            if end == window_end && window_end < edited_length {
+                break;
+            }
+            if end >= after_edit && (end == edited_length || boundaries.binary_search(&to_base(end)).is_ok()) {
+                return Resync { distance: Some(end - position), changed_chunks: changed_chunks + 1 };
+            }
+            // A chunk that ends before the edit is a chunk of the base input when its end is a base boundary too.
+            if end > position || boundaries.binary_search(&end).is_err() {
+                changed_chunks += 1;
+            }
+        }
+        Resync { distance: None, changed_chunks }
+    }
+
+    /// Applies `samples` edits of the length at random positions of the base input, one at a time.
+    /// The positions are taken from the seed, and the inserted bytes from the generator.
+    /// A deletion can't be longer than the base input.
+    pub fn summarize(
+        &self,
+        edit: ShiftEdit,
+        edit_size: usize,
+        samples: usize,
+        seed: u64,
+        generator: &mut Generator,
+    ) -> ResyncSummary {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        rng.set_stream(POSITIONS_STREAM);
+        let mut distances = Vec::with_capacity(samples);
+        let (mut changed_chunks, mut max_changed_chunks) = (0, 0);
+        for _ in 0..samples {
+            let (position, bytes) = match edit {
+                ShiftEdit::Insert => (rng.gen_range(0..=self.data.len()), generator.bytes(edit_size)),
+                ShiftEdit::Delete => {
+                    let position = rng.gen_range(0..=self.data.len() - edit_size);
+                    (position, self.data[position..position + edit_size].to_vec())
+                }
+            };
+            let resync = self.measure_resync(edit, position, &bytes);
+            distances.extend(resync.distance);
+            changed_chunks += resync.changed_chunks;
+            max_changed_chunks = max_changed_chunks.max(resync.changed_chunks);
+        }
+        distances.sort_unstable();
+        ResyncSummary {
+            samples,
+            resynced: distances.len(),
+            mean_distance: distances.iter().sum::<usize>() as f64 / distances.len() as f64,
+            // The nearest rank: the smallest distance that is not less than 90% of the distances.
+            p90_distance: (distances.len() * 9).div_ceil(10).checked_sub(1).map(|rank| distances[rank]),
+            mean_changed_chunks: changed_chunks as f64 / samples as f64,
+            max_changed_chunks,
+        }
+    }
+}
+
+/// Prints how far the boundaries of every chunker shift after single insertions and deletions.
+pub fn print_resilience(
+    avg_sizes: Vec<usize>,
+    avg_size_to_chunk_sizes: AvgSizeToSizes,
+    chunkers_with_names: Vec<NamedChunker>,
+    base: &[u8],
+    settings: &ShiftSettings,
+    generator: &Generator,
+) {
+    let runs = chunkers_with_names
+        .into_iter()
+        .flat_map(|chunker| {
+            let chunk_sizes = avg_sizes.iter().flat_map(|avg_size| avg_size_to_chunk_sizes(*avg_size));
+            std::iter::repeat(chunker).zip(chunk_sizes)
+        })
+        .collect::<Vec<_>>();
+    let edits = settings
+        .edits
+        .iter()
+        .flat_map(|&edit| settings.edit_sizes.iter().map(move |&edit_size| (edit, edit_size)))
+        .filter(|&(edit, edit_size)| match edit {
+            ShiftEdit::Insert => true,
+            ShiftEdit::Delete if edit_size <= base.len() => true,
+            ShiftEdit::Delete => {
+                eprintln!("Skipping the deletions of {}B, which are longer than the base input", edit_size);
+                false
+            }
+        })
+        .collect::<Vec<_>>();
+    let summaries = runs
+        .par_iter()
+        .map(|((name, chunker_builder), chunk_sizes)| {
+            if let Err(e) = chunker_builder(*chunk_sizes) {
+                eprintln!("Skipping {} {}: {}", name, chunk_sizes, e);
+                return Vec::new();
+            }
+            let new_chunker = || chunker_builder(*chunk_sizes).expect("The chunker is built before");
+            let limit = settings.limit_chunks * chunk_sizes.max_size();
+            let chunked_base = ChunkedBase::new(&new_chunker, *chunk_sizes, base, limit);
+            edits
+                .iter()
+                .map(|&(edit, edit_size)| {
+                    // Every run inserts the same bytes.
+                    let mut generator = generator.clone();
+                    let summary =
+                        chunked_base.summarize(edit, edit_size, settings.samples, settings.seed, &mut generator);
+                    (edit, edit_size, summary)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let format_distance = |distance: f64| if distance.is_nan() { "-".to_string() } else { format!("{:.0}", distance) };
+    let format_p90 = |distance: Option<usize>| distance.map_or("-".to_string(), |distance| distance.to_string());
+    println!("| Chunker | Sizes | Edit | Resynced | Mean bytes | P90 bytes | Mean chunks | Max chunks |");
+    println!("|---|---|---|---|---|---|---|---|");
+    for (((name, _), chunk_sizes), summaries) in runs.iter().zip(summaries) {
+        for (edit, edit_size, summary) in summaries {
+            let edit = edit.to_possible_value().expect("No skipped edits");
+            println!(
+                "| {} | {} | {} {}B | {}/{} | {} | {} | {:.2} | {} |",
+                name,
+                chunk_sizes,
+                edit.get_name(),
+                edit_size,
+                summary.resynced,
+                summary.samples,
+                format_distance(summary.mean_distance),
+                format_p90(summary.p90_distance),
+                summary.mean_changed_chunks,
+                summary.max_changed_chunks
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdc_comparison::util::{KB, MB};
+    use cdc_comparison::{build_chunker, ChunkSizes};
+
+    use crate::benchmark::resilience::{ChunkedBase, ShiftEdit};
+    use crate::benchmark::synthetic::{Content, Generator};
+
+    #[test]
+    fn should_resync_after_a_shift_only_with_content_defined_boundaries() {
+        let mut generator = Generator::new(Content::Random, 25);
+        let base = generator.bytes(4 * MB);
+        let sizes = ChunkSizes::new(4 * KB, 8 * KB, 32 * KB);
+        let limit = 16 * sizes.max_size();
+
+        let fixed = || build_chunker("fixed", sizes).unwrap();
+        let summary =
+            ChunkedBase::new(&fixed, sizes, &base, limit).summarize(ShiftEdit::Insert, 1, 50, 25, &mut generator);
+        // Only the edits close to the end of the input resync, at the end.
+        assert!(summary.resynced * 2 < summary.samples, "{}", summary.resynced);
+        assert!(summary.mean_changed_chunks > 16.0, "{}", summary.mean_changed_chunks);
+
+        let gear = || build_chunker("gear:width=64", sizes).unwrap();
+        let chunked_base = ChunkedBase::new(&gear, sizes, &base, limit);
+        for edit in [ShiftEdit::Insert, ShiftEdit::Delete] {
+            let summary = chunked_base.summarize(edit, 64, 200, 25, &mut generator);
+            assert_eq!(summary.resynced, summary.samples);
+            assert!(summary.mean_changed_chunks < 2.5, "{}", summary.mean_changed_chunks);
+            assert!(summary.mean_distance < 2.0 * sizes.avg_size() as f64, "{}", summary.mean_distance);
+        }
+    }
+
+    #[test]
+    fn should_take_the_edit_positions_from_the_seed() {
+        let mut generator = Generator::new(Content::Random, 25);
+        let base = generator.bytes(4 * MB);
+        let sizes = ChunkSizes::new(4 * KB, 8 * KB, 32 * KB);
+        let gear = || build_chunker("gear:width=64", sizes).unwrap();
+        let chunked_base = ChunkedBase::new(&gear, sizes, &base, 16 * sizes.max_size());
+
+        let summarize = |seed| chunked_base.summarize(ShiftEdit::Delete, 64, 100, seed, &mut generator.clone());
+        assert_eq!(summarize(1).mean_distance, summarize(1).mean_distance);
+        assert_ne!(summarize(1).mean_distance, summarize(2).mean_distance);
+
+        // Only the edits within a max size of the end resync, and none of the positions of the seed is there.
+        let fixed = || build_chunker("fixed", sizes).unwrap();
+        let summary = ChunkedBase::new(&fixed, sizes, &base, sizes.max_size()).summarize(
+            ShiftEdit::Insert,
+            1,
+            20,
+            2,
+            &mut generator,
+        );
+        assert_eq!(summary.resynced, 0);
+        assert_eq!(summary.p90_distance, None);
+    }
+
+    #[test]
+    fn should_chunk_every_edit_with_a_new_chunker() {
+        let mut generator = Generator::new(Content::Random, 25);
+        let base = generator.bytes(4 * MB);
+        let sizes = ChunkSizes::new(4 * KB, 8 * KB, 32 * KB);
+        let summarize = |spec: &str| {
+            let chunker = || build_chunker(spec, sizes).unwrap();
+            let chunked_base = ChunkedBase::new(&chunker, sizes, &base, 16 * sizes.max_size());
+            chunked_base.summarize(ShiftEdit::Delete, 64, 100, 25, &mut generator.clone())
+        };
+
+        // QuickCDC doesn't remember the chunks of the base input, so it cuts the random input like its fallback.
+        let (quick_cdc, fast_cdc) = (summarize("quickcdc"), summarize("fastcdc2020"));
+        assert_eq!(quick_cdc.mean_distance, fast_cdc.mean_distance);
+        assert_eq!(quick_cdc.p90_distance, fast_cdc.p90_distance);
+        assert_eq!(quick_cdc.mean_changed_chunks, fast_cdc.mean_changed_chunks);
+    }
+}
@@ -0,0 +1,214 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cdc_comparison::util::MB;
+use clap::ValueEnum;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// The kind of the bytes of a synthetic input and of the inserted and overwritten bytes.
+#[derive(Copy, Clone, ValueEnum)]
+pub enum Content {
+    /// Uniformly random bytes.
+    Random,
+    /// Runs of a repeated byte, mostly zero, with short random segments between them.
+    LowEntropy,
+    /// Words of a random vocabulary, where a few words are much more frequent than the others.
+    Text,
+}
+
+/// The number of edits of every kind per MB of the previous version.
+#[derive(Copy, Clone)]
+pub struct EditRates {
+    pub insertions: f64,
+    pub deletions: f64,
+    pub overwrites: f64,
+    /// A move cuts a range and inserts it at another position.
+    pub moves: f64,
+    /// The edits are from one byte up to this length.
+    pub max_edit_size: usize,
+}
+
+/// An edit at a position of the previous version.
+enum Edit {
+    Insert(Vec<u8>),
+    Delete(usize),
+    Overwrite(Vec<u8>),
+}
+
+const VOCABULARY_SIZE: usize = 2048;
+
+/// Produces reproducible inputs from a ChaCha seed.
+#[derive(Clone)]
+pub struct Generator {
+    content: Content,
+    rng: ChaCha20Rng,
+    vocabulary: Vec<Vec<u8>>,
+}
+
+impl Generator {
+    pub fn new(content: Content, seed: u64) -> Self {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let vocabulary = (0..VOCABULARY_SIZE)
+            .map(|_| (0..rng.gen_range(2..=10)).map(|_| rng.gen_range(b'a'..=b'z')).collect())
+            .collect();
+        Self { content, rng, vocabulary }
+    }
+
+    pub fn bytes(&mut self, length: usize) -> Vec<u8> {
+        let mut result = Vec::with_capacity(length);
+        match self.content {
+            Content::Random => {
+                result.resize(length, 0);
+                self.rng.fill(result.as_mut_slice());
+            }
+            Content::LowEntropy => {
+                while result.len() < length {
+                    if self.rng.gen_ratio(1, 8) {
+                        let segment = self.rng.gen_range(16..=256);
+                        result.extend((0..segment).map(|_| self.rng.gen::<u8>()));
+                    } else {
+                        let byte = if self.rng.gen_bool(0.5) { 0 } else { self.rng.gen() };
+                        let run = self.rng.gen_range(1..=4096);
+                        result.resize(result.len() + run, byte);
+                    }
+                }
+            }
+            Content::Text => {
+                while result.len() < length {
+                    // The cube of a uniform number makes the first words of the vocabulary the most frequent.
+                    let index = (self.rng.gen::<f64>().powi(3) * VOCABULARY_SIZE as f64) as usize;
+                    result.extend_from_slice(&self.vocabulary[index]);
+                    result.push(if self.rng.gen_ratio(1, 12) { b'\n' } else { b' ' });
+                }
+            }
+        }
+        result.truncate(length);
+        result
+    }
+
+    /// Applies the edits at random positions of the data. The edits don't overlap:
+    /// an edit that starts inside the range of the previous one is moved to the end of that range.
+    /// A deletion or an overwrite that is shifted this way is cut at the end of the data,
+    /// so a move whose deletion is cut makes the data longer.
+    pub fn edit(&mut self, data: &[u8], rates: EditRates) -> Vec<u8> {
+        let count = |rate: f64| (rate * data.len() as f64 / MB as f64).round() as usize;
+        let mut edits = Vec::new();
+        for _ in 0..count(rates.insertions) {
+            let length = self.rng.gen_range(1..=rates.max_edit_size);
+            edits.push((self.rng.gen_range(0..=data.len()), Edit::Insert(self.bytes(length))));
+        }
+        for _ in 0..count(rates.deletions) {
+            let (position, length) = self.random_range(data.len(), rates.max_edit_size);
+            edits.push((position, Edit::Delete(length)));
+        }
+        for _ in 0..count(rates.overwrites) {
+            let (position, length) = self.random_range(data.len(), rates.max_edit_size);
+            edits.push((position, Edit::Overwrite(self.bytes(length))));
+        }
+        for _ in 0..count(rates.moves) {
+            let (position, length) = self.random_range(data.len(), rates.max_edit_size);
+            edits.push((position, Edit::Delete(length)));
+            let destination = self.rng.gen_range(0..=data.len());
+            edits.push((destination, Edit::Insert(data[position..position + length].to_vec())));
+        }
+        edits.sort_by_key(|(position, _)| *position);
+
+        let mut result = Vec::with_capacity(data.len());
+        let mut cursor = 0;
+        for (position, edit) in edits {
+            let position = position.max(cursor);
+            result.extend_from_slice(&data[cursor..position]);
+            cursor = position;
+            match edit {
+                Edit::Insert(bytes) => result.extend_from_slice(&bytes),
+                Edit::Delete(length) => cursor = (position + length).min(data.len()),
+                Edit::Overwrite(bytes) => {
+                    let length = bytes.len().min(data.len() - position);
+                    result.extend_from_slice(&bytes[..length]);
+                    cursor = position + length;
+                }
+            }
+        }
+        result.extend_from_slice(&data[cursor..]);
+        result
+    }
+
+    fn random_range(&mut self, data_length: usize, max_length: usize) -> (usize, usize) {
+        let length = self.rng.gen_range(1..=max_length).min(data_length);
+        (self.rng.gen_range(0..=data_length - length), length)
+    }
+}
+
+/// Writes the first version and the edited versions into `version-<n>.bin` files of the output directory,
+/// and returns the paths of the files. The files are the inputs of one dataset.
+pub fn generate_versions(
+    content: Content,
+    seed: u64,
+    size: usize,
+    versions: usize,
+    rates: EditRates,
+    output_dir: &Path,
+) -> std::io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(output_dir)?;
+    let mut generator = Generator::new(content, seed);
+    let mut data = generator.bytes(size);
+    let mut paths = Vec::with_capacity(versions);
+    for version in 0..versions {
+        if version > 0 {
+            data = generator.edit(&data, rates);
+        }
+        let path = output_dir.join(format!("version-{}.bin", version));
+        fs::write(&path, &data)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use cdc_comparison::util::{KB, MB};
+
+    use crate::benchmark::synthetic::{Content, EditRates, Generator};
+
+    #[test]
+    fn should_generate_the_same_versions_from_the_seed() {
+        for content in [Content::Random, Content::LowEntropy, Content::Text] {
+            let rates = EditRates { insertions: 2.0, deletions: 1.0, overwrites: 1.0, moves: 1.0, max_edit_size: KB };
+            let generate = |seed| {
+                let mut generator = Generator::new(content, seed);
+                let base = generator.bytes(4 * MB);
+                let edited = generator.edit(&base, rates);
+                (base, edited)
+            };
+            let (base, edited) = generate(24);
+            assert_eq!(base.len(), 4 * MB);
+            assert!(base != edited);
+            assert!(generate(24) == (base.clone(), edited));
+            assert!(generate(25).0 != base);
+        }
+    }
+
+    const NO_EDITS: EditRates =
+        EditRates { insertions: 0.0, deletions: 0.0, overwrites: 0.0, moves: 0.0, max_edit_size: 1 };
+
+    #[test]
+    fn should_apply_the_edits_of_every_kind() {
+        let mut generator = Generator::new(Content::Random, 24);
+        let base = generator.bytes(MB);
+        assert!(generator.edit(&base, NO_EDITS) == base);
+
+        let insertions = EditRates { insertions: 10.0, ..NO_EDITS };
+        assert!(generator.edit(&base, insertions).len() > base.len());
+        let deletions = EditRates { deletions: 10.0, max_edit_size: KB, ..NO_EDITS };
+        assert!(generator.edit(&base, deletions).len() < base.len());
+        let overwrites = EditRates { overwrites: 10.0, max_edit_size: KB, ..NO_EDITS };
+        let overwritten = generator.edit(&base, overwrites);
+        assert_eq!(overwritten.len(), base.len());
+        assert!(overwritten != base);
+        let moves = EditRates { moves: 10.0, max_edit_size: KB, ..NO_EDITS };
+        let moved = generator.edit(&base, moves);
+        assert!(moved.len() >= base.len());
+        assert!(moved != base);
+    }
+}
@@ -1,8 +1,8 @@
-use crate::chunkers::Chunker;
+use crate::chunkers::{Chunker, SplitProbability};
 use crate::hashes::RollingHash;
 use crate::hashes::RollingHashBuilder;
 use crate::util::chunk_sizes::ChunkSizes;
-use crate::util::mask_builder::MaskBuilder;
+use crate::util::mask_builder::{simple_mask_probability, MaskBuilder, MaskProbability};
 use crate::util::unsigned_integer::UnsignedInteger;
 
 type CenterFinder = fn(&ChunkSizes, usize) -> usize;
@@ -21,6 +21,7 @@ pub fn new_normalized_chunker<T: UnsignedInteger, H: RollingHashBuilder<T>>(
         normalization_level,
         simple_center_finder,
         simple_predicate,
+        Box::new(simple_mask_probability),
     )
 }
 
@@ -38,6 +39,7 @@ pub fn new_normalized_chunker_with_center<T: UnsignedInteger, H: RollingHashBuil
         normalization_level,
         center_finder,
         simple_predicate,
+        Box::new(simple_mask_probability),
     )
 }
 
@@ -47,6 +49,7 @@ pub fn new_normalized_chunker_with_predicate<T: UnsignedInteger, H: RollingHashB
     mask_builder: MaskBuilder<MT>,
     normalization_level: u32,
     predicate: Predicate<T, MT>,
+    mask_probability: MaskProbability<MT>,
 ) -> ChunkerWithMask<T, H, MT> {
     new_custom_normalized_chunker(
         chunk_sizes,
@@ -55,6 +58,7 @@ pub fn new_normalized_chunker_with_predicate<T: UnsignedInteger, H: RollingHashB
         normalization_level,
         simple_center_finder,
         predicate,
+        mask_probability,
     )
 }
 
@@ -65,13 +69,18 @@ pub fn new_custom_normalized_chunker<T: UnsignedInteger, H: RollingHashBuilder<T
     normalization_level: u32,
     center_finder: CenterFinder,
     predicate: Predicate<T, MT>,
+    mask_probability: MaskProbability<MT>,
 ) -> ChunkerWithMask<T, H, MT> {
+    let mask_low_probability = mask_builder(chunk_sizes.avg_size() << normalization_level);
+    let mask_high_probability = mask_builder(chunk_sizes.avg_size() >> normalization_level);
     ChunkerWithMask {
         hash_builder,
         center_finder,
         predicate,
-        mask_low_probability: mask_builder(chunk_sizes.avg_size() << normalization_level),
-        mask_high_probability: mask_builder(chunk_sizes.avg_size() >> normalization_level),
+        mask_low_probability,
+        mask_high_probability,
+        split_probabilities: (mask_probability(mask_low_probability), mask_probability(mask_high_probability)),
+        regression_masks: Vec::new(),
     }
 }
 
@@ -93,6 +102,39 @@ pub struct ChunkerWithMask<T: UnsignedInteger, H: RollingHashBuilder<T>, MT: Uns
     predicate: Predicate<T, MT>,
     mask_low_probability: MT,
     mask_high_probability: MT,
+    /// The probabilities that the low and the high probability masks match a random digest.
+    split_probabilities: (f64, f64),
+    /// The masks of the regression chunking, from the one with the most bits to the one with the least bits.
+    regression_masks: Vec<MT>,
+}
+
+impl<T: UnsignedInteger, H: RollingHashBuilder<T>, MT: UnsignedInteger> ChunkerWithMask<T, H, MT> {
+    /// Enables the regression chunking https://www.usenix.org/system/files/conference/atc12/atc12-final293.pdf
+    ///
+    /// The masks for the halves of the avg size down to `avg/2^levels` are checked where the main mask doesn't match.
+    /// When the max size is reached, the chunk is cut at the last match of the mask with the most bits,
+    /// like the RC4 of https://github.com/dbaarda/rollsum-chunking/blob/master/RESULTS.rst does.
+    /// The chunk is truncated at the max size only when none of the masks matches.
+    pub fn with_regression(mut self, chunk_sizes: ChunkSizes, mask_builder: MaskBuilder<MT>, levels: u32) -> Self {
+        self.regression_masks = (1..=levels).map(|level| mask_builder(chunk_sizes.avg_size() >> level)).collect();
+        self
+    }
+
+    /// Remembers the index when a regression mask matches the digest.
+    fn update_regression(&self, digest: T, index: usize, regression_indices: &mut [Option<usize>]) {
+        let Some(&weakest_mask) = self.regression_masks.last() else {
+            return;
+        };
+        if !(self.predicate)(digest, weakest_mask) {
+            return;
+        }
+        for (mask, regression_index) in self.regression_masks.iter().zip(regression_indices.iter_mut()) {
+            if (self.predicate)(digest, *mask) {
+                *regression_index = Some(index);
+                return;
+            }
+        }
+    }
 }
 
 impl<T: UnsignedInteger, H: RollingHashBuilder<T>, MT: UnsignedInteger> Chunker for ChunkerWithMask<T, H, MT> {
@@ -103,11 +145,13 @@ impl<T: UnsignedInteger, H: RollingHashBuilder<T>, MT: UnsignedInteger> Chunker
             .hash_builder
             .new_hash(&buf[(chunk_sizes.min_size() - self.hash_builder.prepare_bytes_count())..chunk_sizes.min_size()]);
 
+        let mut regression_indices = vec![None; self.regression_masks.len()];
         let mut index = chunk_sizes.min_size();
         while index < center {
             if (self.predicate)(hash.digest(), self.mask_low_probability) {
                 return index;
             }
+            self.update_regression(hash.digest(), index, &mut regression_indices);
             hash.roll(buf[index]);
             index += 1;
         }
@@ -115,10 +159,78 @@ impl<T: UnsignedInteger, H: RollingHashBuilder<T>, MT: UnsignedInteger> Chunker
             if (self.predicate)(hash.digest(), self.mask_high_probability) {
                 return index;
             }
+            self.update_regression(hash.digest(), index, &mut regression_indices);
             hash.roll(buf[index]);
             index += 1;
         }
 
-        index
+        // The buffer is shorter than the max size only at the end of the input, where the tail is a chunk anyway.
+        if index == chunk_sizes.max_size() {
+            regression_indices.into_iter().flatten().next().unwrap_or(index)
+        } else {
+            index
+        }
+    }
+
+    fn split_probability(&self, chunk_sizes: &ChunkSizes) -> Option<SplitProbability> {
+        let (before_center, after_center) = self.split_probabilities;
+        let center = (self.center_finder)(chunk_sizes, chunk_sizes.max_size());
+        Some(SplitProbability { center, before_center, after_center })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::chunker_with_normalization::new_normalized_chunker;
+    use crate::chunkers::{new_buz, Chunker};
+    use crate::hashes::buzhash::BuzHashBuilder;
+    use crate::hashes::tables::sha256_u64_table;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::mask_builder::create_simple_mask;
+    use crate::util::slice_chunks::SliceChunks;
+    use crate::util::KB;
+
+    #[test]
+    fn should_cut_at_regression_points_instead_of_the_max_size() {
+        let mut data = vec![0u8; 8 * 1024 * KB];
+        ChaCha20Rng::seed_from_u64(15).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 24 * KB);
+        let regression =
+            new_buz(sizes, sha256_u64_table(), 64, 0).with_regression(sizes, Box::new(create_simple_mask), 4);
+        let truncating = new_buz(sizes, sha256_u64_table(), 64, 0);
+
+        let max_size_count = |chunks: SliceChunks| chunks.filter(|chunk| chunk.length == sizes.max_size()).count();
+        let regression_count = max_size_count(SliceChunks::new(&data, &regression, sizes));
+        let truncated_count = max_size_count(SliceChunks::new(&data, &truncating, sizes));
+        assert!(truncated_count > 50, "{}", truncated_count);
+        assert!(regression_count * 10 < truncated_count, "{} {}", regression_count, truncated_count);
+    }
+
+    #[test]
+    fn should_prefer_the_regression_mask_with_the_most_bits() {
+        // The digest of the Buzhash with the window of a single byte and the identity table is the last byte.
+        let identity_hash = BuzHashBuilder::new(std::array::from_fn(|i| i as u32), 1);
+        let sizes = ChunkSizes::new(16, 64, 128);
+        let chunker = new_normalized_chunker(sizes, identity_hash, Box::new(create_simple_mask), 0).with_regression(
+            sizes,
+            Box::new(create_simple_mask),
+            2,
+        );
+        // The main mask is 0x3F, and the regression masks are 0x1F and 0x0F.
+        let mut buf = vec![1u8; sizes.max_size()];
+        assert_eq!(chunker.find_split_point(&buf, &sizes), sizes.max_size());
+
+        buf[50] = 0x10;
+        buf[90] = 0x10;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), 91);
+
+        buf[30] = 0x20;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), 31);
+
+        buf[110] = 0x40;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), 111);
     }
 }
@@ -0,0 +1,94 @@
+use crate::chunkers::Chunker;
+use crate::util::chunk_sizes::ChunkSizes;
+
+/// Asymmetric Extremum chunking https://ieeexplore.ieee.org/document/7218510
+///
+/// The chunker doesn't hash the bytes. It cuts at the point that follows a local maximum by `window_size` points,
+/// when no point in between is bigger than the maximum. The window is on the right side of the maximum only,
+/// which is the asymmetry that lets the chunker compare every point only once.
+/// The value of a point is the 8 bytes that start at it, like in the original implementation,
+/// because the single bytes are equal too often for the maximum to be unique.
+///
+/// The distance from the min size to the cut point is `(e-1)*window_size` on average for random data,
+/// so the window is derived from the avg and min sizes.
+pub struct Ae {
+    window_size: usize,
+}
+
+/// The number of bytes in the value of a point.
+const VALUE_SIZE: usize = 8;
+
+impl Ae {
+    pub fn new(chunk_sizes: ChunkSizes) -> Self {
+        let window_size = (chunk_sizes.avg_size() - chunk_sizes.min_size()) as f64 / (std::f64::consts::E - 1.0);
+        Self { window_size: (window_size.round() as usize).max(1) }
+    }
+}
+
+fn value_at(buf: &[u8], i: usize) -> u64 {
+    u64::from_be_bytes(buf[i..i + VALUE_SIZE].try_into().expect("The value has 8 bytes"))
+}
+
+impl Chunker for Ae {
+    fn find_split_point(&self, buf: &[u8], chunk_sizes: &ChunkSizes) -> usize {
+        if buf.len() < chunk_sizes.min_size() + VALUE_SIZE {
+            return buf.len();
+        }
+        let end = buf.len() - VALUE_SIZE + 1;
+        let mut max_position = chunk_sizes.min_size();
+        let mut max_value = value_at(buf, max_position);
+        let mut i = max_position + 1;
+        while i < end {
+            let value = value_at(buf, i);
+            if value > max_value {
+                max_value = value;
+                max_position = i;
+            } else if i == max_position + self.window_size {
+                return i;
+            }
+            i += 1;
+        }
+        buf.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::custom::ae::Ae;
+    use crate::chunkers::Chunker;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::slice_chunks::SliceChunks;
+    use crate::util::{KB, MB};
+
+    #[test]
+    fn should_cut_the_window_after_the_maximum() {
+        let sizes = ChunkSizes::new(KB, 2 * KB, 8 * KB);
+        let chunker = Ae::new(sizes);
+        let window_size = chunker.window_size;
+        let mut buf = vec![0u8; sizes.max_size()];
+        buf[sizes.min_size() + 100] = 0x80;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), sizes.min_size() + 100 + window_size);
+
+        // A bigger point inside the window becomes the maximum, and the window starts again after it.
+        buf[sizes.min_size() + 100 + window_size / 2] = 0xFF;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), sizes.min_size() + 100 + window_size / 2 + window_size);
+
+        // A bigger point right after the window is too late.
+        buf[sizes.min_size() + 100 + window_size / 2 + window_size + 1] = 0xFF;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), sizes.min_size() + 100 + window_size / 2 + window_size);
+    }
+
+    #[test]
+    fn should_split_random_data_into_chunks_of_the_avg_size() {
+        let mut data = vec![0u8; 16 * MB];
+        ChaCha20Rng::seed_from_u64(11).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 64 * KB);
+
+        let chunk_count = SliceChunks::new(&data, &Ae::new(sizes), sizes).count();
+        let avg_size = data.len() / chunk_count;
+        assert!((15 * KB..17 * KB).contains(&avg_size), "{}", avg_size);
+    }
+}
@@ -1,16 +1,11 @@
-use crate::chunkers::Chunker;
+use crate::chunkers::{Chunker, SplitProbability};
 use crate::util::chunk_sizes::ChunkSizes;
 use byteorder::{BigEndian, ReadBytesExt};
 use ring::digest::{Context, SHA256};
 use std::io::Cursor;
 
 fn rol32(x: u32, i: usize) -> u32 {
-    let i = i % 32;
-    if i == 0 {
-        x
-    } else {
-        (x << i) | (x >> (32 - i))
-    }
+    x.rotate_left((i % 32) as u32)
 }
 
 fn generate_table() -> [u32; 256] {
@@ -81,4 +76,8 @@ impl Chunker for Buzhash32Reg {
             i
         }
     }
+
+    fn split_probability(&self, chunk_sizes: &ChunkSizes) -> Option<SplitProbability> {
+        Some(SplitProbability::uniform(chunk_sizes, (self.threshold as f64 + 1.0) / (u32::MAX as f64 + 1.0)))
+    }
 }
@@ -1,16 +1,11 @@
-use crate::chunkers::Chunker;
+use crate::chunkers::{Chunker, SplitProbability};
 use crate::util::chunk_sizes::ChunkSizes;
 use byteorder::{BigEndian, ReadBytesExt};
 use ring::digest::{Context, SHA256};
 use std::io::Cursor;
 
 pub fn rol64(x: u64, i: usize) -> u64 {
-    let i = i % 64;
-    if i == 0 {
-        x
-    } else {
-        (x << i) | (x >> (64 - i))
-    }
+    x.rotate_left((i % 64) as u32)
 }
 
 fn generate_table() -> [u64; 256] {
@@ -81,4 +76,8 @@ impl Chunker for Buzhash64Reg {
             i
         }
     }
+
+    fn split_probability(&self, chunk_sizes: &ChunkSizes) -> Option<SplitProbability> {
+        Some(SplitProbability::uniform(chunk_sizes, (self.threshold as f64 + 1.0) / (u64::MAX as f64 + 1.0)))
+    }
 }
@@ -0,0 +1,135 @@
+use crate::chunkers::Chunker;
+use crate::util::chunk_sizes::ChunkSizes;
+
+/// Local Maximum Chunking, also known as MAXP https://doi.org/10.1016/j.jcss.2009.09.001
+///
+/// The chunker cuts at the point whose value is strictly bigger than the values of all the points
+/// within the `horizon` on both sides. The decision needs the points after the cut point,
+/// so a cut point is only found when the buffer has `horizon` points after it.
+/// Otherwise the chunk is cut at the max size, like the other chunkers do.
+///
+/// The value of a point is the 8 bytes that start at it, because the single bytes are equal too often.
+/// The left side of a point is cut at the start of the chunk, so a point within the horizon of the start
+/// has to be bigger than all the points before it. When the min size is not bigger than the horizon,
+/// the first local maximum of random data is about two horizons after the start of the chunk,
+/// so the horizon is a half of the avg size. With a bigger min size, the first local maximum is
+/// about 8/7 of the horizon after the min size.
+pub struct Lmc {
+    horizon: usize,
+}
+
+/// The number of bytes in the value of a point.
+const VALUE_SIZE: usize = 8;
+
+impl Lmc {
+    pub fn new(chunk_sizes: ChunkSizes) -> Self {
+        let (min_size, avg_size) = (chunk_sizes.min_size(), chunk_sizes.avg_size());
+        let horizon = if min_size <= avg_size / 2 { avg_size / 2 } else { (avg_size - min_size) * 7 / 8 };
+        Self { horizon: horizon.max(1) }
+    }
+}
+
+fn value_at(buf: &[u8], i: usize) -> u64 {
+    u64::from_be_bytes(buf[i..i + VALUE_SIZE].try_into().expect("The value has 8 bytes"))
+}
+
+impl Chunker for Lmc {
+    fn find_split_point(&self, buf: &[u8], chunk_sizes: &ChunkSizes) -> usize {
+        if buf.len() < chunk_sizes.min_size() + VALUE_SIZE {
+            return buf.len();
+        }
+        let end = buf.len() - VALUE_SIZE + 1;
+        // The biggest point since the last point that can't be a local maximum.
+        let mut candidate = chunk_sizes.min_size();
+        let mut candidate_value = value_at(buf, candidate);
+        let mut i = candidate + 1;
+        while i < end {
+            let value = value_at(buf, i);
+            if value >= candidate_value {
+                candidate = i;
+                candidate_value = value;
+            } else if i == candidate + self.horizon {
+                // The right side is smaller. The points after the candidate within the horizon are smaller than it,
+                // so none of them is a local maximum even when the left side is not smaller.
+                let left = candidate.saturating_sub(self.horizon);
+                if (left..candidate).all(|j| value_at(buf, j) < candidate_value) {
+                    return candidate;
+                }
+                i += 1;
+                if i < end {
+                    candidate = i;
+                    candidate_value = value_at(buf, i);
+                }
+            }
+            i += 1;
+        }
+        buf.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::custom::lmc::{value_at, Lmc};
+    use crate::chunkers::Chunker;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::slice_chunks::SliceChunks;
+    use crate::util::{KB, MB};
+
+    #[test]
+    fn should_cut_at_local_maximums() {
+        let mut data = vec![0u8; 16 * 1024 * KB];
+        ChaCha20Rng::seed_from_u64(13).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(8 * KB, 16 * KB, 64 * KB);
+        let chunker = Lmc::new(sizes);
+
+        let chunks = SliceChunks::new(&data, &chunker, sizes);
+        for chunk in chunks.filter(|chunk| chunk.length < sizes.max_size()).take(100) {
+            let end = chunk.offset + chunk.length;
+            let max_value = value_at(&data, end);
+            let left = end.saturating_sub(chunker.horizon).max(chunk.offset);
+            let others = (left..=end + chunker.horizon).filter(|&j| j != end);
+            assert!(others.map(|j| value_at(&data, j)).all(|value| value < max_value));
+        }
+    }
+
+    #[test]
+    fn should_cut_at_the_point_that_is_bigger_than_the_horizon_on_both_sides() {
+        let sizes = ChunkSizes::new(64, 128, 1024);
+        let chunker = Lmc::new(sizes);
+        assert_eq!(chunker.horizon, 64);
+        let mut buf = vec![0u8; sizes.max_size()];
+        buf[100] = 0x80;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), 100);
+
+        // A bigger point on the right side.
+        buf[130] = 0xFF;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), 130);
+
+        // A bigger point on the left side, before the min size.
+        buf[130] = 0;
+        buf[50] = 0xFF;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), buf.len());
+        buf[300] = 0xC0;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), 300);
+    }
+
+    #[test]
+    fn should_split_random_data_into_chunks_of_the_avg_size() {
+        let mut data = vec![0u8; 16 * MB];
+        ChaCha20Rng::seed_from_u64(11).fill(data.as_mut_slice());
+        let cases = [
+            ChunkSizes::new(4 * KB, 16 * KB, 64 * KB),
+            ChunkSizes::new(8 * KB, 16 * KB, 64 * KB),
+            ChunkSizes::new(12 * KB, 16 * KB, 64 * KB),
+            ChunkSizes::new(16 * KB, 64 * KB, 256 * KB),
+        ];
+        for sizes in cases {
+            let chunk_count = SliceChunks::new(&data, &Lmc::new(sizes), sizes).count();
+            let avg_size = data.len() / chunk_count;
+            assert!(avg_size.abs_diff(sizes.avg_size()) < sizes.avg_size() / 14, "{} {}", sizes, avg_size);
+        }
+    }
+}
@@ -1,2 +1,8 @@
+pub mod ae;
 pub mod buzhash32_reg;
 pub mod buzhash64_reg;
+pub mod lmc;
+pub mod quick_cdc;
+pub mod ram;
+pub mod seq_cdc;
+pub mod tttd;
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::chunkers::{Chunker, SplitProbability};
+use crate::util::chunk_sizes::ChunkSizes;
+
+/// QuickCDC, from "QuickCDC: A Quick Content Defined Chunking Algorithm Based on Jumping and Dynamically Adjusting Mask Bits"
+///
+/// The chunker remembers the first and the last bytes of the chunks that it has found, together with their lengths.
+/// When a chunk starts with known first bytes, the chunker checks the last bytes at the remembered lengths,
+/// and cuts there without hashing the bytes in between. Otherwise the cut point is found by the `fallback` chunker,
+/// and the new chunk is remembered.
+///
+/// The jumps pay off when the input repeats the content that was chunked before,
+/// like the second version of a tarball after the first one.
+/// The chunker is stateful, so a new chunker is needed for every input that should be chunked independently.
+pub struct QuickCdc<C: Chunker> {
+    fallback: C,
+    /// The lengths and the end features of the chunks by their front features.
+    chunks_by_front: Mutex<HashMap<u64, Vec<(usize, u64)>>>,
+}
+
+/// The number of bytes in the front and the end features.
+const FEATURE_SIZE: usize = 8;
+/// The number of the latest chunks that are remembered for the same front feature.
+const MAX_CANDIDATES: usize = 4;
+
+impl<C: Chunker> QuickCdc<C> {
+    pub fn new(fallback: C) -> Self {
+        Self { fallback, chunks_by_front: Mutex::new(HashMap::new()) }
+    }
+}
+
+fn feature_at(buf: &[u8], i: usize) -> u64 {
+    u64::from_le_bytes(buf[i..i + FEATURE_SIZE].try_into().expect("The feature has 8 bytes"))
+}
+
+impl<C: Chunker> Chunker for QuickCdc<C> {
+    fn find_split_point(&self, buf: &[u8], chunk_sizes: &ChunkSizes) -> usize {
+        if buf.len() < chunk_sizes.min_size().max(FEATURE_SIZE) {
+            return self.fallback.find_split_point(buf, chunk_sizes);
+        }
+        let front = feature_at(buf, 0);
+        let chunks_by_front = self.chunks_by_front.lock().expect("The lock is not poisoned");
+        let jump = chunks_by_front.get(&front).and_then(|candidates| {
+            candidates.iter().rev().find(|&&(length, end)| {
+                length <= buf.len() && length >= chunk_sizes.min_size() && feature_at(buf, length - FEATURE_SIZE) == end
+            })
+        });
+        if let Some(&(length, _)) = jump {
+            return length;
+        }
+        drop(chunks_by_front);
+
+        let index = self.fallback.find_split_point(buf, chunk_sizes);
+        // A chunk that ends with the buffer before the max size is the tail of the input, which isn't content-defined.
+        if index >= FEATURE_SIZE && (index < buf.len() || buf.len() == chunk_sizes.max_size()) {
+            let mut chunks_by_front = self.chunks_by_front.lock().expect("The lock is not poisoned");
+            let candidates = chunks_by_front.entry(front).or_default();
+            if candidates.len() == MAX_CANDIDATES {
+                candidates.remove(0);
+            }
+            candidates.push((index, feature_at(buf, index - FEATURE_SIZE)));
+        }
+        index
+    }
+
+    /// The jumps happen only on the repeated content, so random input is cut by the fallback.
+    fn split_probability(&self, chunk_sizes: &ChunkSizes) -> Option<SplitProbability> {
+        self.fallback.split_probability(chunk_sizes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::custom::quick_cdc::QuickCdc;
+    use crate::chunkers::ported::fast_cdc2020::FastCdc2020;
+    use crate::chunkers::Chunker;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::slice_chunks::SliceChunks;
+    use crate::util::KB;
+
+    struct CountingChunker(FastCdc2020, AtomicUsize);
+
+    impl Chunker for CountingChunker {
+        fn find_split_point(&self, buf: &[u8], chunk_sizes: &ChunkSizes) -> usize {
+            self.1.fetch_add(1, Ordering::Relaxed);
+            self.0.find_split_point(buf, chunk_sizes)
+        }
+    }
+
+    #[test]
+    fn should_jump_over_the_repeated_content() {
+        let mut data = vec![0u8; 4 * 1024 * KB];
+        ChaCha20Rng::seed_from_u64(17).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 64 * KB);
+        let chunker = QuickCdc::new(CountingChunker(FastCdc2020::new(sizes, 2), AtomicUsize::new(0)));
+
+        let first = SliceChunks::new(&data, &chunker, sizes).map(|chunk| chunk.length).collect::<Vec<_>>();
+        let fallback_calls = chunker.fallback.1.load(Ordering::Relaxed);
+        assert_eq!(fallback_calls, first.len() - 1);
+        let second = SliceChunks::new(&data, &chunker, sizes).map(|chunk| chunk.length).collect::<Vec<_>>();
+        assert_eq!(first, second);
+        // Only the tail chunk is not remembered.
+        assert_eq!(chunker.fallback.1.load(Ordering::Relaxed), fallback_calls + 1);
+
+        let fast_cdc = FastCdc2020::new(sizes, 2);
+        let expected = SliceChunks::new(&data, &fast_cdc, sizes).map(|chunk| chunk.length).collect::<Vec<_>>();
+        assert_eq!(first, expected);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::chunkers::Chunker;
+use crate::util::chunk_sizes::ChunkSizes;
+
+/// Rapid Asymmetric Maximum chunking https://doi.org/10.1016/j.future.2017.02.013
+///
+/// The chunker doesn't hash the bytes. It takes the maximum byte of a fixed window at the start of the chunk,
+/// and cuts after the first byte that follows the window and is not smaller than the maximum.
+/// The window is skipped with a single pass that only compares bytes, and the search after it usually stops fast.
+///
+/// The maximum of a big window of random bytes is 255, so the search takes 256 bytes on average.
+/// The window is the avg size without these bytes, but it is never smaller than the min size.
+pub struct Ram {
+    window_size: usize,
+}
+
+/// The average number of bytes after the window of random data.
+const EXPECTED_SEARCH_LENGTH: usize = 256;
+
+impl Ram {
+    pub fn new(chunk_sizes: ChunkSizes) -> Self {
+        let window_size = chunk_sizes.avg_size().saturating_sub(EXPECTED_SEARCH_LENGTH).max(chunk_sizes.min_size());
+        Self { window_size: window_size.max(1) }
+    }
+}
+
+impl Chunker for Ram {
+    fn find_split_point(&self, buf: &[u8], _chunk_sizes: &ChunkSizes) -> usize {
+        if buf.len() <= self.window_size {
+            return buf.len();
+        }
+        let max_value = *buf[..self.window_size].iter().max().expect("The window is not empty");
+        match buf[self.window_size..].iter().position(|&byte| byte >= max_value) {
+            Some(position) => self.window_size + position + 1,
+            None => buf.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::custom::ram::Ram;
+    use crate::chunkers::Chunker;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::slice_chunks::SliceChunks;
+    use crate::util::{KB, MB};
+
+    #[test]
+    fn should_cut_after_the_first_byte_that_reaches_the_window_maximum() {
+        let sizes = ChunkSizes::new(KB, 2 * KB, 8 * KB);
+        let chunker = Ram::new(sizes);
+        let window_size = chunker.window_size;
+        let mut buf = vec![0u8; sizes.max_size()];
+        // Any byte reaches the maximum of a window of zeros.
+        assert_eq!(chunker.find_split_point(&buf, &sizes), window_size + 1);
+
+        buf[5] = 200;
+        buf[window_size + 10] = 199;
+        buf[window_size + 20] = 200;
+        buf[window_size + 30] = 255;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), window_size + 21);
+
+        buf[window_size + 20] = 0;
+        buf[window_size + 30] = 0;
+        assert_eq!(chunker.find_split_point(&buf, &sizes), buf.len());
+    }
+
+    #[test]
+    fn should_split_random_data_into_chunks_of_the_avg_size() {
+        let mut data = vec![0u8; 16 * MB];
+        ChaCha20Rng::seed_from_u64(12).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 64 * KB);
+
+        let chunker = Ram::new(sizes);
+        let chunks = SliceChunks::new(&data, &chunker, sizes).collect::<Vec<_>>();
+        let avg_size = data.len() / chunks.len();
+        assert!((15 * KB..17 * KB).contains(&avg_size), "{}", avg_size);
+        // The search after the window takes about 256 bytes, so few chunks reach the max size.
+        let max_size_chunks = chunks.iter().filter(|chunk| chunk.length == sizes.max_size()).count();
+        assert!(max_size_chunks < chunks.len() / 100, "{}", max_size_chunks);
+    }
+}
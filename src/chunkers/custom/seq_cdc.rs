@@ -0,0 +1,155 @@
+use serde::Deserialize;
+
+use crate::chunkers::Chunker;
+use crate::util::chunk_sizes::ChunkSizes;
+
+/// The direction of the byte sequences that make a cut point.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeqOrder {
+    Increasing,
+    Decreasing,
+}
+
+/// SeqCDC https://doi.org/10.1145/3652892.3700766
+///
+/// The chunker doesn't hash the bytes. It cuts after `SEQUENCE_LENGTH` consecutive byte pairs
+/// that are strictly increasing or decreasing. The pairs in the opposite direction are counted,
+/// and after `SKIP_TRIGGER` of them the chunker jumps over `skip_size` bytes,
+/// because the content that doesn't have the sequences is unlikely to have them soon.
+///
+/// The sequence length and the skip trigger are the ones of the paper.
+/// The skip size is derived from the sizes, so that the cut point is `avg-min` bytes after the min size
+/// for random data, see [compared_bytes_per_cut] and [skips_per_cut].
+pub struct SeqCdc {
+    order: SeqOrder,
+    skip_size: usize,
+}
+
+const SEQUENCE_LENGTH: usize = 5;
+const SKIP_TRIGGER: usize = 50;
+
+impl SeqCdc {
+    pub fn new(chunk_sizes: ChunkSizes, order: SeqOrder) -> Self {
+        let compared_bytes = compared_bytes_per_cut();
+        let distance = ((chunk_sizes.avg_size() - chunk_sizes.min_size()) as f64 - compared_bytes).max(0.0);
+        let skip_size = (distance / skips_per_cut(compared_bytes)).round() as usize;
+        Self { order, skip_size }
+    }
+
+    fn in_sequence(&self, previous: u8, current: u8) -> bool {
+        match self.order {
+            SeqOrder::Increasing => current > previous,
+            SeqOrder::Decreasing => current < previous,
+        }
+    }
+}
+
+/// The probability that `length` random bytes are strictly increasing, which is the same as strictly decreasing.
+fn monotonic_probability(length: usize) -> f64 {
+    (0..length).map(|i| (256 - i) as f64 / 256.0 / (i + 1) as f64).product()
+}
+
+/// The number of the byte pairs that are compared before a cut point of random data, which is about 887.
+///
+/// A cut point ends a run of `m = SEQUENCE_LENGTH + 1` strictly monotonic bytes. The expected number of the bytes
+/// until the first such run is `1 / Σ (p(jm) - p(jm+1))` over `j >= 1`, where `p(k)` is the probability
+/// that `k` bytes are monotonic (F. N. David, D. E. Barton, Combinatorial Chance, 1962).
+fn compared_bytes_per_cut() -> f64 {
+    let m = SEQUENCE_LENGTH + 1;
+    let sum = (1..=256 / m).map(|j| monotonic_probability(j * m) - monotonic_probability(j * m + 1)).sum::<f64>();
+    1.0 / sum - 1.0
+}
+
+/// The number of the skips before a cut point of random data, which is about 8.4.
+///
+/// A pair is opposing with the probability `1 - p(2)`. The cut point is rare enough to be memoryless,
+/// so the number of the opposing pairs before it is geometric with the mean `μ`,
+/// and the expected number of the whole `SKIP_TRIGGER` multiples among them is `1 / (e^(SKIP_TRIGGER/μ) - 1)`.
+fn skips_per_cut(compared_bytes: f64) -> f64 {
+    let opposing_pairs = (1.0 - monotonic_probability(2)) * compared_bytes;
+    1.0 / ((SKIP_TRIGGER as f64 / opposing_pairs).exp() - 1.0)
+}
+
+impl Chunker for SeqCdc {
+    fn find_split_point(&self, buf: &[u8], chunk_sizes: &ChunkSizes) -> usize {
+        let mut sequence_count = 0;
+        let mut opposing_count = 0;
+        let mut index = chunk_sizes.min_size().max(1);
+        while index < buf.len() {
+            if self.in_sequence(buf[index - 1], buf[index]) {
+                sequence_count += 1;
+                if sequence_count == SEQUENCE_LENGTH {
+                    return index;
+                }
+            } else {
+                sequence_count = 0;
+                opposing_count += 1;
+                if opposing_count == SKIP_TRIGGER {
+                    opposing_count = 0;
+                    index += self.skip_size;
+                }
+            }
+            index += 1;
+        }
+        buf.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::custom::seq_cdc::{compared_bytes_per_cut, skips_per_cut, SeqCdc, SeqOrder};
+    use crate::chunkers::Chunker;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::slice_chunks::SliceChunks;
+    use crate::util::{KB, MB};
+
+    #[test]
+    fn should_derive_the_skip_size_from_the_sizes() {
+        let compared_bytes = compared_bytes_per_cut();
+        assert!((886.0..887.0).contains(&compared_bytes), "{}", compared_bytes);
+        let skips = skips_per_cut(compared_bytes);
+        assert!((8.4..8.5).contains(&skips), "{}", skips);
+        assert_eq!(SeqCdc::new(ChunkSizes::new(4 * KB, 16 * KB, 64 * KB), SeqOrder::Increasing).skip_size, 1356);
+        assert_eq!(SeqCdc::new(ChunkSizes::new(KB, 1536, 4 * KB), SeqOrder::Increasing).skip_size, 0);
+    }
+
+    #[test]
+    fn should_cut_after_the_sequence_and_skip_after_the_opposing_pairs() {
+        let sizes = ChunkSizes::new(16, 64, 1024);
+        let chunker = SeqCdc { order: SeqOrder::Increasing, skip_size: 100 };
+        let mut buf = vec![0u8; sizes.max_size()];
+        // The zero before the sequence makes the fifth increasing pair end at 24.
+        buf[20..25].copy_from_slice(&[1, 2, 3, 4, 5]);
+        assert_eq!(chunker.find_split_point(&buf, &sizes), 24);
+        let decreasing = SeqCdc { order: SeqOrder::Decreasing, skip_size: 100 };
+        assert_eq!(decreasing.find_split_point(&buf, &sizes), buf.len());
+
+        // The equal bytes are opposing pairs. The 50th of them ends at 65, and the next 100 bytes are skipped.
+        let mut buf = vec![0u8; sizes.max_size()];
+        buf[70..75].copy_from_slice(&[1, 2, 3, 4, 5]);
+        buf[200..205].copy_from_slice(&[1, 2, 3, 4, 5]);
+        assert_eq!(chunker.find_split_point(&buf, &sizes), 204);
+    }
+
+    // The skip size is derived for random data, so the chunks keep the avg size for every ratio of the sizes.
+    #[test]
+    fn should_split_random_data_into_chunks_of_the_avg_size() {
+        let mut data = vec![0u8; 16 * MB];
+        ChaCha20Rng::seed_from_u64(16).fill(data.as_mut_slice());
+        let cases = [
+            (SeqOrder::Increasing, ChunkSizes::new(2 * KB, 8 * KB, 32 * KB)),
+            (SeqOrder::Increasing, ChunkSizes::new(4 * KB, 16 * KB, 64 * KB)),
+            (SeqOrder::Decreasing, ChunkSizes::new(4 * KB, 16 * KB, 64 * KB)),
+            (SeqOrder::Decreasing, ChunkSizes::new(16 * KB, 32 * KB, 128 * KB)),
+        ];
+        for (order, sizes) in cases {
+            let chunk_count = SliceChunks::new(&data, &SeqCdc::new(sizes, order), sizes).count();
+            let ratio = data.len() as f64 / chunk_count as f64 / sizes.avg_size() as f64;
+            assert!((0.95..1.05).contains(&ratio), "{} {}", sizes, ratio);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::chunkers::{Chunker, SplitProbability};
+use crate::hashes::{RollingHash, RollingHashBuilder};
+use crate::util::chunk_sizes::ChunkSizes;
+use crate::util::mask_builder::{simple_mask_probability, MaskBuilder};
+use crate::util::unsigned_integer::UnsignedInteger;
+
+/// Two Thresholds, Two Divisors chunking https://www.hpl.hp.com/techreports/2005/HPL-2005-30R1.pdf
+///
+/// The min and max sizes are the thresholds. The main divisor is the mask of the avg size.
+/// The backup divisor is the mask of a half of the avg size, so it matches twice as often.
+/// The last backup match is remembered, and the chunk is cut there instead of at the max size
+/// when the main mask doesn't match before the max size.
+pub struct Tttd<T: UnsignedInteger, H: RollingHashBuilder<T>> {
+    hash_builder: H,
+    main_mask: T,
+    backup_mask: T,
+}
+
+impl<T: UnsignedInteger, H: RollingHashBuilder<T>> Tttd<T, H> {
+    pub fn new(chunk_sizes: ChunkSizes, hash_builder: H, mask_builder: MaskBuilder<T>) -> Self {
+        Self {
+            hash_builder,
+            main_mask: mask_builder(chunk_sizes.avg_size()),
+            backup_mask: mask_builder(chunk_sizes.avg_size() / 2),
+        }
+    }
+}
+
+impl<T: UnsignedInteger, H: RollingHashBuilder<T>> Chunker for Tttd<T, H> {
+    fn find_split_point(&self, buf: &[u8], chunk_sizes: &ChunkSizes) -> usize {
+        let mut hash = self
+            .hash_builder
+            .new_hash(&buf[(chunk_sizes.min_size() - self.hash_builder.prepare_bytes_count())..chunk_sizes.min_size()]);
+
+        let mut backup_index = None;
+        let mut index = chunk_sizes.min_size();
+        while index < buf.len() {
+            let digest = hash.digest();
+            if (digest & self.main_mask) == T::zero() {
+                return index;
+            }
+            if (digest & self.backup_mask) == T::zero() {
+                backup_index = Some(index);
+            }
+            hash.roll(buf[index]);
+            index += 1;
+        }
+
+        // The buffer is shorter than the max size only at the end of the input, where the tail is a chunk anyway.
+        if index == chunk_sizes.max_size() {
+            backup_index.unwrap_or(index)
+        } else {
+            index
+        }
+    }
+
+    /// The backup divisor only moves the cut at the max size.
+    fn split_probability(&self, chunk_sizes: &ChunkSizes) -> Option<SplitProbability> {
+        Some(SplitProbability::uniform(chunk_sizes, simple_mask_probability(self.main_mask)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::custom::tttd::Tttd;
+    use crate::chunkers::{new_buz, Chunker};
+    use crate::hashes::buzhash::BuzHashBuilder;
+    use crate::hashes::tables::sha256_u64_table;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::mask_builder::create_simple_mask;
+    use crate::util::slice_chunks::SliceChunks;
+    use crate::util::KB;
+
+    #[test]
+    fn should_cut_at_the_backup_point_instead_of_the_max_size() {
+        let mut data = vec![0u8; 8 * 1024 * KB];
+        ChaCha20Rng::seed_from_u64(14).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 24 * KB);
+        let tttd = Tttd::new(sizes, BuzHashBuilder::new(sha256_u64_table(), 64), Box::new(create_simple_mask));
+        let truncating = new_buz(sizes, sha256_u64_table(), 64, 0);
+
+        let max_size_count = |chunks: SliceChunks| chunks.filter(|chunk| chunk.length == sizes.max_size()).count();
+        let tttd_count = max_size_count(SliceChunks::new(&data, &tttd, sizes));
+        let truncated_count = max_size_count(SliceChunks::new(&data, &truncating, sizes));
+        assert!(truncated_count > 50, "{}", truncated_count);
+        assert!(tttd_count * 2 < truncated_count, "{} {}", tttd_count, truncated_count);
+    }
+
+    #[test]
+    fn should_cut_at_the_last_backup_match_at_the_max_size() {
+        // The digest of the Buzhash with the window of a single byte and the identity table is the last byte.
+        let identity_hash = || BuzHashBuilder::new(std::array::from_fn(|i| i as u32), 1);
+        let sizes = ChunkSizes::new(16, 64, 128);
+        let tttd = Tttd::new(sizes, identity_hash(), Box::new(create_simple_mask));
+        // The main mask is 0x3F and the backup mask is 0x1F.
+        let mut buf = vec![1u8; sizes.max_size()];
+        assert_eq!(tttd.find_split_point(&buf, &sizes), sizes.max_size());
+
+        buf[40] = 0x20;
+        buf[70] = 0x20;
+        assert_eq!(tttd.find_split_point(&buf, &sizes), 71);
+        // The input ends before the max size.
+        assert_eq!(tttd.find_split_point(&buf[..100], &sizes), 100);
+
+        buf[100] = 0x40;
+        assert_eq!(tttd.find_split_point(&buf, &sizes), 101);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::chunkers::Chunker;
 use crate::util::chunk_sizes::ChunkSizes;
 
+#[derive(Default)]
 pub struct Fixed;
 
 /// Splits inputs into chunks of a fixed average size.
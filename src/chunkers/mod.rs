@@ -1,9 +1,11 @@
 use crate::chunkers::chunker_with_normalization::{new_normalized_chunker, ChunkerWithMask};
 use crate::hashes::adler32::Adler32Builder;
 use crate::hashes::buzhash::BuzHashBuilder;
+use crate::hashes::crc::CrcHashBuilder;
 use crate::hashes::gearhash::GearHashBuilder;
 use crate::hashes::polynomial_hash::polynomial::Pol;
 use crate::hashes::polynomial_hash::PolynomialHashBuilder;
+use crate::hashes::rabin_karp::RabinKarpBuilder;
 use crate::util::chunk_sizes::ChunkSizes;
 use crate::util::mask_builder::{create_simple_mask, create_spread_mask};
 use crate::util::unsigned_integer::UnsignedInteger;
@@ -12,12 +14,38 @@ pub mod chunker_with_normalization;
 pub mod custom;
 pub mod fixed_size;
 pub mod ported;
+pub mod registry;
 
-pub trait Chunker {
+/// The chunkers are shared between threads and async tasks.
+/// A chunker that learns from the chunks it has found keeps the state behind a lock,
+/// and a new chunker is built for every input that is chunked independently.
+pub trait Chunker: Send + Sync {
     /// Accepts a buffer and chunk sizes.
     /// The buffer is always of size [min;max).
     /// Returns the index in the buffer so that the [0;index) is the new chunk.
     fn find_split_point(&self, buf: &[u8], chunk_sizes: &ChunkSizes) -> usize;
+
+    /// The probability that a byte of random input is a split point, which the masks of the chunker assume.
+    /// It is `None` for the chunkers that don't cut with a fixed probability per byte.
+    fn split_probability(&self, _chunk_sizes: &ChunkSizes) -> Option<SplitProbability> {
+        None
+    }
+}
+
+/// The per byte split probability of a chunker before and after the normalization center.
+/// The probabilities are equal for the chunkers without the normalization.
+#[derive(Copy, Clone, Debug)]
+pub struct SplitProbability {
+    /// The chunk length from which the probability after the center applies.
+    pub center: usize,
+    pub before_center: f64,
+    pub after_center: f64,
+}
+
+impl SplitProbability {
+    pub fn uniform(chunk_sizes: &ChunkSizes, probability: f64) -> Self {
+        Self { center: chunk_sizes.avg_size(), before_center: probability, after_center: probability }
+    }
 }
 
 pub fn new_polynomial(
@@ -34,6 +62,47 @@ pub fn new_polynomial(
     )
 }
 
+/// The lowest bits of the Rabin-Karp hash are weak like in the Gear hash, so the mask is spread.
+pub fn new_rabin_karp<T: UnsignedInteger>(
+    chunk_sizes: ChunkSizes,
+    base: T,
+    window_size: usize,
+    normalization_level: u32,
+) -> ChunkerWithMask<T, RabinKarpBuilder<T>, T> {
+    new_normalized_chunker(
+        chunk_sizes,
+        RabinKarpBuilder::new(base, window_size),
+        Box::new(create_spread_mask),
+        normalization_level,
+    )
+}
+
+pub fn new_crc32c(
+    chunk_sizes: ChunkSizes,
+    window_size: usize,
+    normalization_level: u32,
+) -> ChunkerWithMask<u32, CrcHashBuilder<u32>, u32> {
+    new_normalized_chunker(
+        chunk_sizes,
+        CrcHashBuilder::crc32c(window_size),
+        Box::new(create_simple_mask),
+        normalization_level,
+    )
+}
+
+pub fn new_crc64_xz(
+    chunk_sizes: ChunkSizes,
+    window_size: usize,
+    normalization_level: u32,
+) -> ChunkerWithMask<u64, CrcHashBuilder<u64>, u64> {
+    new_normalized_chunker(
+        chunk_sizes,
+        CrcHashBuilder::crc64_xz(window_size),
+        Box::new(create_simple_mask),
+        normalization_level,
+    )
+}
+
 pub fn new_buz<T: UnsignedInteger>(
     chunk_sizes: ChunkSizes,
     table: [T; 256],
@@ -56,7 +56,7 @@ use crate::util::chunk_sizes::ChunkSizes;
 use crate::util::mask_builder::create_simple_mask;
 
 #[rustfmt::skip]
-const BORG_TABLE: [u32; 256] = [
+pub(crate) const BORG_TABLE: [u32; 256] = [
     0xe7f831ec, 0xf4026465, 0xafb50cae, 0x6d553c7a, 0xd639efe3, 0x19a7b895, 0x9aba5b21, 0x5417d6d4,
     0x35fd2b84, 0xd1f6a159, 0x3f8e323f, 0xb419551c, 0xf444cebf, 0x21dc3b80, 0xde8d1e36, 0x84a32436,
     0xbeb35a9d, 0xa36f24aa, 0xa4e60186, 0x98d18ffe, 0x3f042f9e, 0xdb228bcd, 0x096474b7, 0x5c20c2f7,
@@ -91,10 +91,11 @@ const BORG_TABLE: [u32; 256] = [
     0xc5ae37bb, 0xa76ce12a, 0x8150d8f3, 0x2ec29218, 0xa35f0984, 0x48c0647e, 0x0b5ff98c, 0x71893f7b
 ];
 
-const WINDOW_SIZE: usize = 4095;
+pub const WINDOW_SIZE: usize = 4095;
 
 pub struct Borg;
 
+#[allow(clippy::new_ret_no_self)]
 impl Borg {
     pub fn new(chunk_sizes: ChunkSizes) -> ChunkerWithMask<u32, BuzHashBuilder<u32>, u32> {
         new_normalized_chunker(
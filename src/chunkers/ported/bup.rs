@@ -0,0 +1,156 @@
+// This code is ported from the https://github.com/bup/bup/blob/main/lib/bup/bupsplit.c
+// See the license in [crate::hashes::rollsum].
+
+use crate::chunkers::{Chunker, SplitProbability};
+use crate::hashes::rollsum::Rollsum;
+use crate::hashes::RollingHash;
+use crate::util::chunk_sizes::ChunkSizes;
+use crate::util::logarithm2;
+
+/// The bup chunker, which cuts where the lowest `blob_bits` bits of the second sum of the [Rollsum] are all ones.
+/// bup uses 13 bits, which is the avg size of 8KB.
+///
+/// Unlike the chunkers with a mask, the checksum starts at the beginning of every chunk with the window of zero bytes,
+/// like in bup. bup has no min size, so the cut points before the min size are skipped,
+/// which makes the chunks bigger than the avg size by the min size.
+pub struct Bup {
+    blob_bits: u32,
+}
+
+impl Bup {
+    pub fn new(chunk_sizes: ChunkSizes) -> Self {
+        Self { blob_bits: logarithm2(chunk_sizes.avg_size() as u32) }
+    }
+}
+
+fn is_split_point(hash: &Rollsum, blob_bits: u32) -> bool {
+    let mask = (1 << blob_bits) - 1;
+    hash.s2() & mask == mask
+}
+
+/// The `bupsplit_find_ofs` of bup. Returns the length of the first chunk of the buffer
+/// and the number of bits that bup uses for the fanout of the hash split tree.
+/// The bits are the `blob_bits` and the number of consecutive ones above the next bit of the digest.
+pub fn find_ofs(buf: &[u8], blob_bits: u32) -> Option<(usize, u32)> {
+    let mut hash = Rollsum::new();
+    for (count, &byte) in buf.iter().enumerate() {
+        hash.roll(byte);
+        if is_split_point(&hash, blob_bits) {
+            let mut rsum = hash.digest() >> blob_bits;
+            let mut bits = blob_bits;
+            loop {
+                rsum >>= 1;
+                if rsum & 1 == 0 {
+                    break;
+                }
+                bits += 1;
+            }
+            return Some((count + 1, bits));
+        }
+    }
+    None
+}
+
+impl Chunker for Bup {
+    fn find_split_point(&self, buf: &[u8], chunk_sizes: &ChunkSizes) -> usize {
+        let mut hash = Rollsum::new();
+        for (count, &byte) in buf.iter().enumerate() {
+            hash.roll(byte);
+            if count + 1 >= chunk_sizes.min_size() && is_split_point(&hash, self.blob_bits) {
+                return count + 1;
+            }
+        }
+        buf.len()
+    }
+
+    fn split_probability(&self, chunk_sizes: &ChunkSizes) -> Option<SplitProbability> {
+        Some(SplitProbability::uniform(chunk_sizes, 0.5f64.powi(self.blob_bits as i32)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::ported::bup::{find_ofs, Bup};
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::slice_chunks::SliceChunks;
+    use crate::util::{KB, MB};
+
+    #[test]
+    fn should_split_like_bup_without_the_min_size() {
+        let mut data = vec![0u8; 8 * MB];
+        ChaCha20Rng::seed_from_u64(18).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(0, 8 * KB, 8 * MB);
+
+        let mut offset = 0;
+        let mut bits_counts = [0; 3];
+        for chunk in SliceChunks::new(&data, &Bup::new(sizes), sizes) {
+            assert_eq!(chunk.offset, offset);
+            match find_ofs(&data[offset..], 13) {
+                Some((length, bits)) => {
+                    assert_eq!(chunk.length, length);
+                    bits_counts[(bits - 13).min(2) as usize] += 1;
+                }
+                None => assert_eq!(chunk.offset + chunk.length, data.len()),
+            }
+            offset += chunk.length;
+        }
+        let chunk_count: usize = bits_counts.iter().sum();
+        let avg_size = data.len() / chunk_count;
+        assert!((7 * KB..9 * KB).contains(&avg_size), "{}", avg_size);
+        // Every next bit is set in a half of the digests.
+        assert!(bits_counts[0].abs_diff(bits_counts[1] + bits_counts[2]) < chunk_count / 10, "{:?}", bits_counts);
+        assert!(bits_counts[1].abs_diff(bits_counts[2]) < chunk_count / 10, "{:?}", bits_counts);
+    }
+
+    /// The xorshift32 generator of Marsaglia, so that the input is easy to reproduce for bup.
+    fn xorshift_bytes(length: usize) -> Vec<u8> {
+        let mut state = 2463534242u32;
+        (0..length)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    // The chunks and the bits of `bupsplit_find_ofs` with 13 blob bits.
+    // They are computed with a Python transcription of bupsplit.c.
+    #[test]
+    fn should_split_at_the_offsets_of_bup() {
+        let data = xorshift_bytes(128 * KB);
+        let expected = [
+            (1665, 13),
+            (2037, 13),
+            (10034, 13),
+            (824, 14),
+            (4662, 13),
+            (20279, 14),
+            (30040, 14),
+            (752, 13),
+            (4001, 15),
+            (14360, 16),
+            (8527, 14),
+            (7651, 13),
+            (6409, 13),
+            (5323, 13),
+        ];
+
+        let mut offset = 0;
+        for (length, bits) in expected {
+            assert_eq!(find_ofs(&data[offset..], 13), Some((length, bits)));
+            offset += length;
+        }
+        assert_eq!(find_ofs(&data[offset..], 13), None);
+
+        let sizes = ChunkSizes::new(0, 8 * KB, 128 * KB);
+        let lengths = SliceChunks::new(&data, &Bup::new(sizes), sizes).map(|chunk| chunk.length).collect::<Vec<_>>();
+        let mut expected_lengths = expected.map(|(length, _)| length).to_vec();
+        expected_lengths.push(14508);
+        assert_eq!(lengths, expected_lengths);
+    }
+}
@@ -20,7 +20,7 @@ use crate::util::chunk_sizes::ChunkSizes;
 use crate::util::mask_builder::MaskBuilder;
 
 #[rustfmt::skip]
-const CASYNC_TABLE: [u32; 256] = [
+pub(crate) const CASYNC_TABLE: [u32; 256] = [
     0x458be752, 0xc10748cc, 0xfbbcdbb8, 0x6ded5b68,
     0xb10a82b5, 0x20d75648, 0xdfc5665f, 0xa8428801,
     0x7ebf5191, 0x841135c7, 0x65cc53b3, 0x280a597c,
@@ -87,25 +87,31 @@ const CASYNC_TABLE: [u32; 256] = [
     0x7bf7cabc, 0xf9c18d66, 0x593ade65, 0xd95ddf11,
 ];
 
-const WINDOW_SIZE: usize = 48;
+pub const WINDOW_SIZE: usize = 48;
 
 pub struct Casync;
 
+#[allow(clippy::new_ret_no_self)]
 impl Casync {
     pub fn new(chunk_sizes: ChunkSizes) -> ChunkerWithMask<u32, BuzHashBuilder<u32>, u64> {
-        let mask_builder: MaskBuilder<u64> = Box::new(move |_target_size| {
-            (chunk_sizes.avg_size() as f64 / (-1.42888852e-7 * chunk_sizes.avg_size() as f64 + 1.33237515)) as u64
-        });
+        let mask_builder: MaskBuilder<u64> = Box::new(move |_target_size| discriminator(chunk_sizes.avg_size()));
         new_normalized_chunker_with_predicate(
             chunk_sizes,
             BuzHashBuilder::new(CASYNC_TABLE, WINDOW_SIZE),
             mask_builder,
             0,
             casync_predicate,
+            Box::new(|discriminator| 1.0 / discriminator as f64),
         )
     }
 }
 
+/// The divisor of the digest that gives the desired average size.
+/// It is zero for the average sizes above roughly 9MB, which casync doesn't support.
+pub fn discriminator(avg_size: usize) -> u64 {
+    (avg_size as f64 / (-1.42888852e-7 * avg_size as f64 + 1.33237515)) as u64
+}
+
 fn casync_predicate(digest: u32, discriminator: u64) -> bool {
     (digest as u64 % discriminator) == (discriminator - 1)
 }
@@ -152,6 +152,7 @@ pub fn create_fastcdc_mask(target_size: usize) -> u64 {
     MASKS[bits_count as usize]
 }
 
+#[allow(clippy::new_ret_no_self)]
 impl FastCdc2016 {
     pub fn new(chunk_sizes: ChunkSizes, normalization_level: u32) -> ChunkerWithMask<u64, GearHashBuilder<u64>, u64> {
         assert!(chunk_sizes.avg_size() >= FAST_CDC_AVERAGE_MIN && chunk_sizes.avg_size() <= FAST_CDC_AVERAGE_MAX);
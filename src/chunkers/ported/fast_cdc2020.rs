@@ -33,13 +33,14 @@
 use crate::chunkers::ported::fast_cdc2016::{
     create_fastcdc_mask, FAST_CDC_2016_TABLE, FAST_CDC_AVERAGE_MAX, FAST_CDC_AVERAGE_MIN,
 };
-use crate::chunkers::Chunker;
+use crate::chunkers::{Chunker, SplitProbability};
 use crate::util::chunk_sizes::ChunkSizes;
+use crate::util::mask_builder::simple_mask_probability;
 
 // GEAR table in which all values have been shifted left 1 bit, as per the
 // FastCDC 2020 paper, section 3.7.
 #[rustfmt::skip]
-const GEAR_LS: [u64; 256] = [
+pub(crate) const GEAR_LS: [u64; 256] = [
     0x76ba78fa40fc6fb8, 0xf09ad1752224610c, 0x9aa5101f105ce530, 0xd59f1c9c33fb994e,
     0x863e70bbf7a2c656, 0x3abe4e003c4b57cc, 0x062617bc7935b322, 0x89644acedd36ec92,
     0x54653653c11d6932, 0x6cff97a43caefab0, 0x004f7555b4559ed6, 0xc7de5ab58e78444c,
@@ -157,4 +158,12 @@ impl Chunker for FastCdc2020 {
 
         index
     }
+
+    fn split_probability(&self, chunk_sizes: &ChunkSizes) -> Option<SplitProbability> {
+        Some(SplitProbability {
+            center: chunk_sizes.avg_size(),
+            before_center: simple_mask_probability(self.mask_s),
+            after_center: simple_mask_probability(self.mask_l),
+        })
+    }
 }
@@ -37,11 +37,11 @@
 //! [2] https://!github.com/dbaarda/rollsum-chunking/blob/master/RESULTS.rst
 //! [3] https://!www.usenix.org/system/files/conference/atc12/atc12-final293.pdf
 
-use crate::chunkers::Chunker;
+use crate::chunkers::{Chunker, SplitProbability};
 use crate::util::chunk_sizes::ChunkSizes;
 
 #[rustfmt::skip]
-const GEAR: [u64; 256] = [
+pub(crate) const GEAR: [u64; 256] = [
     0x651748f5a15f8222, 0xd6eda276c877d8ea, 0x66896ef9591b326b,
     0xcd97506b21370a12, 0x8c9c5c9acbeb2a05, 0xb8b9553ee17665ef,
     0x1784a989315b1de6, 0x947666c9c50df4bd, 0xb3f660ea7ff2d6a4,
@@ -151,7 +151,7 @@ impl Chunker for GoogleStadiaCdc {
         // rounds to make sure the hash has gathered full "content history".
         let mut i = chunk_sizes.min_size() - HASH_BITS;
         while i < chunk_sizes.min_size() {
-            digest = (digest << 1).wrapping_add(GEAR[buf[i as usize] as usize]);
+            digest = (digest << 1).wrapping_add(GEAR[buf[i] as usize]);
             i += 1;
         }
 
@@ -173,7 +173,7 @@ impl Chunker for GoogleStadiaCdc {
                     rc_mask <<= 1;
                 }
             }
-            digest = (digest << 1).wrapping_add(GEAR[buf[i as usize] as usize]);
+            digest = (digest << 1).wrapping_add(GEAR[buf[i] as usize]);
             i += 1;
         }
 
@@ -184,4 +184,8 @@ impl Chunker for GoogleStadiaCdc {
             i
         }
     }
+
+    fn split_probability(&self, chunk_sizes: &ChunkSizes) -> Option<SplitProbability> {
+        Some(SplitProbability::uniform(chunk_sizes, (self.threshold as f64 + 1.0) / (u64::MAX as f64 + 1.0)))
+    }
 }
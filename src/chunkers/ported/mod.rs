@@ -1,4 +1,5 @@
 pub mod borg;
+pub mod bup;
 pub mod casync;
 pub mod fast_cdc2016;
 pub mod fast_cdc2020;
@@ -6,7 +6,7 @@
 //!  Incremental Synchronization," in IEEE Access, vol. 8, pp. 5316-5330, 2020,
 //!  doi: 10.1109/ACCESS.2019.2963625.
 //!  PDF: https://ieeexplore.ieee.org/stamp/stamp.jsp?tp=&arnumber=8949536
-use probability::distribution::{Binomial, Inverse};
+use probability::distribution::{Binomial, Distribution, Inverse};
 
 use crate::chunkers::chunker_with_normalization::{
     new_custom_normalized_chunker, simple_center_finder, ChunkerWithMask,
@@ -32,8 +32,14 @@ pub fn threshold(chunk_size: usize, window_size: usize) -> u32 {
     (d.inverse(1.0 - desired_probability) + 1) as u32
 }
 
+/// The probability that at least `threshold` bits of a random window are ones.
+pub fn threshold_probability(threshold: u32, window_size: usize) -> f64 {
+    1.0 - Binomial::new(8 * window_size, 0.5).distribution(threshold as f64 - 1.0)
+}
+
 pub struct Pci;
 
+#[allow(clippy::new_ret_no_self)]
 impl Pci {
     pub fn new(
         chunk_sizes: ChunkSizes,
@@ -48,6 +54,7 @@ impl Pci {
             normalization_level,
             simple_center_finder,
             |ones_count, threshold| ones_count >= threshold,
+            Box::new(move |threshold| threshold_probability(threshold, window_size)),
         )
     }
 }
@@ -31,10 +31,11 @@ use crate::hashes::polynomial_hash::PolynomialHashBuilder;
 use crate::util::chunk_sizes::ChunkSizes;
 use crate::util::mask_builder::create_simple_mask;
 
-const WINDOW_SIZE: usize = 64;
+pub const WINDOW_SIZE: usize = 64;
 
 pub struct ResticCdc;
 
+#[allow(clippy::new_ret_no_self)]
 impl ResticCdc {
     pub fn new(pol: Pol, chunk_sizes: ChunkSizes) -> ChunkerWithMask<u64, PolynomialHashBuilder, u64> {
         assert!(chunk_sizes.avg_size() <= u32::MAX as usize);
@@ -58,7 +59,7 @@ mod tests {
     use crate::util::chunk_sizes::ChunkSizes;
     use crate::util::chunk_stream::ChunkStream;
     use crate::util::sha256;
-    use crate::{KB, MB};
+    use crate::util::{KB, MB};
 
     #[test]
     pub fn should_split_random_data1() -> std::io::Result<()> {
@@ -87,7 +88,7 @@ mod tests {
             (2651975, "181347d2bbec32bef77ad5e9001e6af80f6abcf3576549384d334ee00c1988d8"),
             (237392, "fcd567f5d866357a8e299fd5b2359bb2c8157c30395229c4e9b0a353944a7978"),
         ];
-        let chunk_sizes = ChunkSizes::new(512 * KB, 1 * MB, 8 * MB);
+        let chunk_sizes = ChunkSizes::new(512 * KB, MB, 8 * MB);
 
         verify_chunks_for_data(File::open("data/test/restic_data1")?, chunks, chunk_sizes);
         Ok(())
@@ -143,7 +144,7 @@ mod tests {
             (min_size, "07854d2fef297a06ba81685e660c332de36d5d18d546927d30daad6d7fda1541"),
             (min_size, "07854d2fef297a06ba81685e660c332de36d5d18d546927d30daad6d7fda1541"),
         ];
-        let chunk_sizes = ChunkSizes::new(min_size, 1 * MB, 8 * MB);
+        let chunk_sizes = ChunkSizes::new(min_size, MB, 8 * MB);
         let zeros = vec![0u8; chunks.len() * min_size];
 
         verify_chunks_for_data(zeros.as_slice(), chunks, chunk_sizes);
@@ -151,17 +152,17 @@ mod tests {
 
     fn verify_chunks_for_data<R: Read>(input: R, chunks: Vec<(usize, &str)>, chunk_sizes: ChunkSizes) {
         let expected_chunks_count = chunks.len();
-        let pol = Pol::from(0x3DA3358B4DC173 as u64);
+        let pol = Pol::from(0x3DA3358B4DC173_u64);
         let restic: Box<dyn Chunker> = Box::new(ResticCdc::new(pol, chunk_sizes));
-        let mut chunker = ChunkStream::new(input, &restic, chunk_sizes);
+        let mut chunker = ChunkStream::new(input, restic.as_ref(), chunk_sizes);
 
         let mut offset = 0;
         let mut chunk_count = 0;
         for (expected_length, expected_sha) in chunks {
-            let chunk = chunker.next().unwrap().unwrap();
+            let chunk = chunker.next_chunk().unwrap().unwrap();
             assert_eq!(chunk.offset, offset);
             assert_eq!(chunk.length, expected_length);
-            assert_eq!(sha256(&chunk.data), expected_sha);
+            assert_eq!(sha256(chunk.data), expected_sha);
             offset += chunk.length;
             chunk_count += 1;
         }
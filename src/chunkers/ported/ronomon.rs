@@ -47,6 +47,7 @@
 //! >   size normalization.
 use crate::chunkers::chunker_with_normalization::{new_normalized_chunker_with_center, ChunkerWithMask};
 use crate::hashes::right_gearhash::RightGearHashBuilder;
+use crate::hashes::tables::keyed_table;
 use crate::util::chunk_sizes::ChunkSizes;
 use crate::util::logarithm2;
 use crate::util::mask_builder::create_simple_mask;
@@ -61,7 +62,7 @@ pub const AVERAGE_MAX: usize = 268435456;
 
 /// https://github.com/ronomon/deduplication/blob/master/binding.cc#L20
 #[rustfmt::skip]
-const RONOMON_TABLE: [u32; 256] = [
+pub(crate) const RONOMON_TABLE: [u32; 256] = [
     0x5c95c078, 0x22408989, 0x2d48a214, 0x12842087, 0x530f8afb, 0x474536b9, 0x2963b4f1, 0x44cb738b,
     0x4ea7403d, 0x4d606b6e, 0x074ec5d3, 0x3af39d18, 0x726003ca, 0x37a62a74, 0x51a2f58e, 0x7506358e,
     0x5d4ab128, 0x4d4ae17b, 0x41e85924, 0x470c36f7, 0x4741cbe1, 0x01bb7f30, 0x617c1de3, 0x2b0c3a1f,
@@ -99,7 +100,7 @@ const RONOMON_TABLE: [u32; 256] = [
 type Aes256Ctr64BE = ctr::Ctr64BE<aes::Aes256>;
 
 /// https://github.com/nlfiedler/fastcdc-rs/blob/0f165fc5fd76e4c9b267bc4fa3a4ec6fcb78fe60/examples/table32.rs
-fn ronomon64_table() -> [u64; 256] {
+pub(crate) fn ronomon64_table() -> [u64; 256] {
     let max_value: u64 = 1u64 << 63;
     let mut table = [0u8; 2048];
     let key = GenericArray::from([0u8; 32]);
@@ -108,17 +109,18 @@ fn ronomon64_table() -> [u64; 256] {
     cipher.apply_keystream(&mut table);
     let mut rdr = Cursor::new(&table[..]);
     let mut result = [0u64; 256];
-    for i in 0..256 {
+    for value in result.iter_mut() {
         let mut num: u64 = rdr.read_u64::<BigEndian>().unwrap();
         num %= max_value;
         assert!(num < max_value);
-        result[i] = num;
+        *value = num;
     }
     result
 }
 
 pub struct RonomonCdc;
 
+#[allow(clippy::new_ret_no_self)]
 impl RonomonCdc {
     pub fn new_original(
         chunk_sizes: ChunkSizes,
@@ -134,6 +136,17 @@ impl RonomonCdc {
         Self::new(ronomon64_table(), chunk_sizes, normalization_level)
     }
 
+    /// Uses the balanced table that is derived from the secret key, see [keyed_table].
+    /// The right Gear hash adds the values to the shifted digest, which doesn't overflow
+    /// only when the highest bit of the values is zero, like in the original tables.
+    pub fn new_keyed<T: UnsignedInteger>(
+        key: &[u8],
+        chunk_sizes: ChunkSizes,
+        normalization_level: u32,
+    ) -> ChunkerWithMask<T, RightGearHashBuilder<T>, T> {
+        Self::new(keyed_table::<T>(key).map(|value| value >> 1), chunk_sizes, normalization_level)
+    }
+
     pub fn new<T: UnsignedInteger>(
         table: [T; 256],
         chunk_sizes: ChunkSizes,
@@ -156,7 +169,7 @@ impl RonomonCdc {
 
 /// Integer division that rounds up instead of down.
 fn ceil_div(x: usize, y: usize) -> usize {
-    (x + y - 1) / y
+    x.div_ceil(y)
 }
 
 /// Find the middle of the desired chunk size, or what the FastCDC paper refers
@@ -0,0 +1,1255 @@
+//! Builds any supported chunker from a spec string.
+//!
+//! A spec string is the family name followed by the comma separated parameters, e.g.
+//! `buzhash:width=64,table=buz,window=256,nc=1,mask=spread`, `fastcdc2020:nc=2` or `restic:pol_seed=7`.
+//! The parameters with default values can be omitted, see [FAMILIES].
+
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use data_encoding::HEXLOWER_PERMISSIVE;
+use itertools::Itertools;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+use crate::chunkers::custom::ae::Ae;
+use crate::chunkers::custom::buzhash32_reg::Buzhash32Reg;
+use crate::chunkers::custom::buzhash64_reg::Buzhash64Reg;
+use crate::chunkers::custom::lmc::Lmc;
+use crate::chunkers::custom::quick_cdc::QuickCdc;
+use crate::chunkers::custom::ram::Ram;
+use crate::chunkers::custom::seq_cdc::{SeqCdc, SeqOrder};
+use crate::chunkers::custom::tttd::Tttd;
+use crate::chunkers::fixed_size::Fixed;
+use crate::chunkers::ported::borg::Borg;
+use crate::chunkers::ported::bup::Bup;
+use crate::chunkers::ported::casync::Casync;
+use crate::chunkers::ported::fast_cdc2016::{FastCdc2016, FAST_CDC_AVERAGE_MAX, FAST_CDC_AVERAGE_MIN};
+use crate::chunkers::ported::fast_cdc2020::FastCdc2020;
+use crate::chunkers::ported::google_stadia_cdc::GoogleStadiaCdc;
+use crate::chunkers::ported::pci::Pci;
+use crate::chunkers::ported::restic::ResticCdc;
+use crate::chunkers::ported::ronomon::RonomonCdc;
+use crate::chunkers::ported::{borg, casync, restic, ronomon};
+use crate::chunkers::{
+    new_adler_u32, new_buz, new_buz_spread_mask, new_crc32c, new_crc64_xz, new_gear_spread_mask, new_polynomial,
+    new_rabin_karp, Chunker,
+};
+use crate::hashes::adler32::Adler32Builder;
+use crate::hashes::buzhash::BuzHashBuilder;
+use crate::hashes::polynomial_hash::polynomial::Pol;
+use crate::hashes::polynomial_hash::PolynomialHashBuilder;
+use crate::hashes::tables::{buz_table, keyed_table, sha256_u128_table, sha256_u32_table, sha256_u64_table};
+use crate::util::chunk_sizes::ChunkSizes;
+use crate::util::logarithm2;
+use crate::util::mask_builder::{create_simple_mask, create_spread_mask};
+use crate::util::unsigned_integer::UnsignedInteger;
+
+/// The lookup table of the table driven hashes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashTable {
+    /// The table from [sha256_u32_table] and its wider variants.
+    Sha256,
+    /// The balanced table from [buz_table].
+    Buz,
+}
+
+/// The way the split mask is created for a target chunk size.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MaskType {
+    /// The lowest bits of the digest, see [crate::util::mask_builder::create_simple_mask].
+    Simple,
+    /// Randomly spread bits of the digest, see [crate::util::mask_builder::create_spread_mask].
+    Spread,
+}
+
+/// The rolling hash of the chunkers that are generic over the hash.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RollingHashType {
+    /// The 64 bit Buzhash with the table from [sha256_u64_table].
+    Buzhash,
+    /// The 64 bit Rabin fingerprint with the polynomial of the [DEFAULT_POL_SEED].
+    Polynomial,
+    Adler32,
+}
+
+impl RollingHashType {
+    /// The number of bits in the digest.
+    fn width(&self) -> u32 {
+        match self {
+            RollingHashType::Buzhash | RollingHashType::Polynomial => 64,
+            RollingHashType::Adler32 => 32,
+        }
+    }
+}
+
+/// The window of the PCI hash.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PciWindow {
+    Bytes(usize),
+    /// The window equal to the min chunk size.
+    Min,
+}
+
+/// Where the secret key of the keyed tables and polynomials is read from when the chunker is built:
+/// `env:<variable>` with the key in hex, or `file:<path>` with the raw bytes of the key.
+/// Only the source is a part of the spec, so the key never gets into the names and the reports.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KeySource {
+    Env(String),
+    File(PathBuf),
+}
+
+/// The shortest accepted key, 128 bits.
+pub const MIN_KEY_LENGTH: usize = 16;
+
+impl<'de> Deserialize<'de> for PciWindow {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Bytes(usize),
+            Text(String),
+        }
+        match Value::deserialize(deserializer)? {
+            Value::Bytes(bytes) => Ok(PciWindow::Bytes(bytes)),
+            Value::Text(text) => text.parse().map_err(D::Error::custom),
+        }
+    }
+}
+
+impl FromStr for HashTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha256" => Ok(HashTable::Sha256),
+            "buz" => Ok(HashTable::Buz),
+            _ => Err(format!("expected 'sha256' or 'buz', got '{}'", s)),
+        }
+    }
+}
+
+impl Display for HashTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashTable::Sha256 => write!(f, "sha256"),
+            HashTable::Buz => write!(f, "buz"),
+        }
+    }
+}
+
+impl FromStr for MaskType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "simple" => Ok(MaskType::Simple),
+            "spread" => Ok(MaskType::Spread),
+            _ => Err(format!("expected 'simple' or 'spread', got '{}'", s)),
+        }
+    }
+}
+
+impl Display for MaskType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaskType::Simple => write!(f, "simple"),
+            MaskType::Spread => write!(f, "spread"),
+        }
+    }
+}
+
+impl FromStr for RollingHashType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "buzhash" => Ok(RollingHashType::Buzhash),
+            "polynomial" => Ok(RollingHashType::Polynomial),
+            "adler32" => Ok(RollingHashType::Adler32),
+            _ => Err(format!("expected 'buzhash', 'polynomial' or 'adler32', got '{}'", s)),
+        }
+    }
+}
+
+impl Display for RollingHashType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RollingHashType::Buzhash => write!(f, "buzhash"),
+            RollingHashType::Polynomial => write!(f, "polynomial"),
+            RollingHashType::Adler32 => write!(f, "adler32"),
+        }
+    }
+}
+
+impl FromStr for SeqOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "increasing" => Ok(SeqOrder::Increasing),
+            "decreasing" => Ok(SeqOrder::Decreasing),
+            _ => Err(format!("expected 'increasing' or 'decreasing', got '{}'", s)),
+        }
+    }
+}
+
+impl Display for SeqOrder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeqOrder::Increasing => write!(f, "increasing"),
+            SeqOrder::Decreasing => write!(f, "decreasing"),
+        }
+    }
+}
+
+impl FromStr for PciWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "min" => Ok(PciWindow::Min),
+            _ => s.parse().map(PciWindow::Bytes).map_err(|_| format!("expected a number or 'min', got '{}'", s)),
+        }
+    }
+}
+
+impl Display for PciWindow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PciWindow::Bytes(bytes) => write!(f, "{}", bytes),
+            PciWindow::Min => write!(f, "min"),
+        }
+    }
+}
+
+impl KeySource {
+    pub fn read(&self) -> Result<Vec<u8>, SpecError> {
+        let key = match self {
+            KeySource::Env(variable) => {
+                let hex = std::env::var(variable)
+                    .map_err(|_| SpecError(format!("the key variable {} is not set", variable)))?;
+                HEXLOWER_PERMISSIVE
+                    .decode(hex.trim().as_bytes())
+                    .map_err(|_| SpecError(format!("the key variable {} is not hex", variable)))?
+            }
+            KeySource::File(path) => {
+                fs::read(path).map_err(|e| SpecError(format!("can't read the key file {}: {}", path.display(), e)))?
+            }
+        };
+        require(key.len() >= MIN_KEY_LENGTH, || {
+            format!("the key of {} is shorter than {} bytes", self, MIN_KEY_LENGTH)
+        })?;
+        Ok(key)
+    }
+}
+
+impl FromStr for KeySource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("env", variable)) if !variable.is_empty() => Ok(KeySource::Env(variable.to_string())),
+            Some(("file", path)) if !path.is_empty() => Ok(KeySource::File(PathBuf::from(path))),
+            _ => Err(format!("expected 'env:<variable>' or 'file:<path>', got '{}'", s)),
+        }
+    }
+}
+
+impl Display for KeySource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeySource::Env(variable) => write!(f, "env:{}", variable),
+            KeySource::File(path) => write!(f, "file:{}", path.display()),
+        }
+    }
+}
+
+/// A fully parameterized chunker.
+/// The name of the chunker in the reports is derived from the parameters,
+/// so that the name never disagrees with the chunker that was actually built.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChunkerSpec {
+    FixedSize,
+    Borg,
+    Casync,
+    FastCdc2016 { nc: u32 },
+    FastCdc2020 { nc: u32 },
+    StadiaCdc,
+    Pci { window: PciWindow, nc: u32 },
+    Restic { pol_seed: u64 },
+    Ronomon { width: u32, nc: u32, key: Option<KeySource> },
+    BuzhashReg { width: u32, window: usize },
+    Buzhash { width: u32, table: HashTable, window: usize, nc: u32, mask: MaskType, rc: u32, key: Option<KeySource> },
+    Gear { width: u32, table: HashTable, nc: u32, rc: u32, key: Option<KeySource> },
+    Adler32 { window: usize, nc: u32, rc: u32 },
+    Polynomial { window: usize, nc: u32, pol_seed: u64, rc: u32, key: Option<KeySource> },
+    Ae,
+    Ram,
+    Lmc,
+    Tttd { hash: RollingHashType, window: usize },
+    SeqCdc { order: SeqOrder },
+    QuickCdc { nc: u32 },
+    Bup,
+    Crc { width: u32, window: usize, nc: u32, rc: u32 },
+    RabinKarp { width: u32, window: usize, base: u64, nc: u32, rc: u32 },
+}
+
+pub const FAST_CDC_DEFAULT_NC: u32 = 2;
+pub const RONOMON_DEFAULT_NC: u32 = 1;
+pub const DEFAULT_POL_SEED: u64 = 1;
+/// The 32 bit FNV prime, which is the default base of the 32 bit Rabin-Karp hash.
+pub const DEFAULT_RABIN_KARP_BASE_32: u64 = 0x01000193;
+/// The 64 bit FNV prime, which is the default base of the 64 and 128 bit Rabin-Karp hashes.
+pub const DEFAULT_RABIN_KARP_BASE_64: u64 = 0x100000001B3;
+
+/// The families with their parameters. The parameters without a default value are required.
+pub const FAMILIES: [(&str, &str); 23] = [
+    ("fixed", ""),
+    ("borg", ""),
+    ("casync", ""),
+    ("fastcdc2016", "nc=2"),
+    ("fastcdc2020", "nc=2"),
+    ("stadia", ""),
+    ("pci", "window=<bytes|min>,nc=0"),
+    ("restic", "pol_seed=1"),
+    ("ronomon", "width=32|64,nc=1,key=<env:variable|file:path>"),
+    ("buzhash_reg", "width=<32|64>,window=<bytes>"),
+    (
+        "buzhash",
+        "width=<32|64|128>,table=sha256|buz,window=<bytes>,nc=0,mask=simple|spread,rc=0,key=<env:variable|file:path>",
+    ),
+    ("gear", "width=<32|64|128>,table=sha256|buz,nc=0,rc=0,key=<env:variable|file:path>"),
+    ("adler32", "window=<bytes>,nc=0,rc=0"),
+    ("polynomial", "window=<bytes>,nc=0,pol_seed=1,rc=0,key=<env:variable|file:path>"),
+    ("ae", ""),
+    ("ram", ""),
+    ("lmc", ""),
+    ("tttd", "hash=buzhash|polynomial|adler32,window=<bytes>"),
+    ("seqcdc", "order=increasing|decreasing"),
+    ("quickcdc", "nc=2"),
+    ("bup", ""),
+    ("crc", "width=<32|64>,window=<bytes>,nc=0,rc=0"),
+    ("rabin_karp", "width=<32|64|128>,window=<bytes>,base=<the FNV prime of the width>,nc=0,rc=0"),
+];
+
+/// An invalid spec string, or parameters that the chunker doesn't support.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpecError(String);
+
+impl Display for SpecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+/// Builds the chunker from a spec string, see [ChunkerSpec::build].
+pub fn build_chunker(spec: &str, sizes: ChunkSizes) -> Result<Box<dyn Chunker>, SpecError> {
+    spec.parse::<ChunkerSpec>()?.build(sizes)
+}
+
+/// Parses a spec string whose parameters can have several values separated by `|`,
+/// e.g. `buzhash:width=32|64,window=64|256`, into the specs of all combinations of the values.
+/// The last parameter changes the fastest.
+pub fn expand_specs(spec: &str) -> Result<Vec<ChunkerSpec>, SpecError> {
+    let (family, params) = spec.split_once(':').unwrap_or((spec, ""));
+    let alternatives = params
+        .split(',')
+        .filter(|param| !param.trim().is_empty())
+        .map(|param| match param.split_once('=') {
+            Some((key, values)) => values.split('|').map(|value| format!("{}={}", key, value)).collect(),
+            None => vec![param.to_string()],
+        })
+        .collect::<Vec<Vec<String>>>();
+    if alternatives.is_empty() {
+        return Ok(vec![spec.parse()?]);
+    }
+    alternatives
+        .into_iter()
+        .multi_cartesian_product()
+        .map(|params| format!("{}:{}", family, params.join(",")).parse())
+        .collect()
+}
+
+impl ChunkerSpec {
+    pub fn family(&self) -> &'static str {
+        match self {
+            ChunkerSpec::FixedSize => "fixed",
+            ChunkerSpec::Borg => "borg",
+            ChunkerSpec::Casync => "casync",
+            ChunkerSpec::FastCdc2016 { .. } => "fastcdc2016",
+            ChunkerSpec::FastCdc2020 { .. } => "fastcdc2020",
+            ChunkerSpec::StadiaCdc => "stadia",
+            ChunkerSpec::Pci { .. } => "pci",
+            ChunkerSpec::Restic { .. } => "restic",
+            ChunkerSpec::Ronomon { .. } => "ronomon",
+            ChunkerSpec::BuzhashReg { .. } => "buzhash_reg",
+            ChunkerSpec::Buzhash { .. } => "buzhash",
+            ChunkerSpec::Gear { .. } => "gear",
+            ChunkerSpec::Adler32 { .. } => "adler32",
+            ChunkerSpec::Polynomial { .. } => "polynomial",
+            ChunkerSpec::Ae => "ae",
+            ChunkerSpec::Ram => "ram",
+            ChunkerSpec::Lmc => "lmc",
+            ChunkerSpec::Tttd { .. } => "tttd",
+            ChunkerSpec::SeqCdc { .. } => "seqcdc",
+            ChunkerSpec::QuickCdc { .. } => "quickcdc",
+            ChunkerSpec::Bup => "bup",
+            ChunkerSpec::Crc { .. } => "crc",
+            ChunkerSpec::RabinKarp { .. } => "rabin_karp",
+        }
+    }
+
+    /// The spec string with all parameters, including the default ones.
+    /// Parsing it gives back the same spec.
+    pub fn spec_string(&self) -> String {
+        let params = match *self {
+            ChunkerSpec::FixedSize
+            | ChunkerSpec::Borg
+            | ChunkerSpec::Casync
+            | ChunkerSpec::StadiaCdc
+            | ChunkerSpec::Ae
+            | ChunkerSpec::Ram
+            | ChunkerSpec::Lmc
+            | ChunkerSpec::Bup => return self.family().to_string(),
+            ChunkerSpec::FastCdc2016 { nc } | ChunkerSpec::FastCdc2020 { nc } | ChunkerSpec::QuickCdc { nc } => {
+                format!("nc={}", nc)
+            }
+            ChunkerSpec::Pci { window, nc } => format!("window={},nc={}", window, nc),
+            ChunkerSpec::Restic { pol_seed } => format!("pol_seed={}", pol_seed),
+            ChunkerSpec::Ronomon { width, nc, ref key } => format!("width={},nc={}{}", width, nc, key_param(key)),
+            ChunkerSpec::BuzhashReg { width, window } => format!("width={},window={}", width, window),
+            ChunkerSpec::Buzhash { width, table, window, nc, mask, rc, ref key } => format!(
+                "width={},table={},window={},nc={},mask={},rc={}{}",
+                width,
+                table,
+                window,
+                nc,
+                mask,
+                rc,
+                key_param(key)
+            ),
+            ChunkerSpec::Gear { width, table, nc, rc, ref key } => {
+                format!("width={},table={},nc={},rc={}{}", width, table, nc, rc, key_param(key))
+            }
+            ChunkerSpec::Adler32 { window, nc, rc } => format!("window={},nc={},rc={}", window, nc, rc),
+            ChunkerSpec::Polynomial { window, nc, pol_seed, rc, ref key } => {
+                format!("window={},nc={},pol_seed={},rc={}{}", window, nc, pol_seed, rc, key_param(key))
+            }
+            ChunkerSpec::Tttd { hash, window } => format!("hash={},window={}", hash, window),
+            ChunkerSpec::Crc { width, window, nc, rc } => {
+                format!("width={},window={},nc={},rc={}", width, window, nc, rc)
+            }
+            ChunkerSpec::RabinKarp { width, window, base, nc, rc } => {
+                format!("width={},window={},base={},nc={},rc={}", width, window, base, nc, rc)
+            }
+            ChunkerSpec::SeqCdc { order } => format!("order={}", order),
+        };
+        format!("{}:{}", self.family(), params)
+    }
+
+    /// Checks the parameters that don't depend on the chunk sizes.
+    fn validate_parameters(&self) -> Result<(), SpecError> {
+        match *self {
+            ChunkerSpec::Ronomon { width, .. }
+            | ChunkerSpec::BuzhashReg { width, .. }
+            | ChunkerSpec::Crc { width, .. } => check_width(width, &[32, 64]),
+            ChunkerSpec::Buzhash { width, table, ref key, .. } | ChunkerSpec::Gear { width, table, ref key, .. } => {
+                check_width(width, &[32, 64, 128])?;
+                require(key.is_none() || table == HashTable::Buz, || {
+                    "the key derives the balanced table, so it requires the buz table".to_string()
+                })
+            }
+            ChunkerSpec::Polynomial { pol_seed, ref key, .. } => {
+                require(key.is_none() || pol_seed == DEFAULT_POL_SEED, || {
+                    "the key derives the polynomial, so it can't be used with a seed".to_string()
+                })
+            }
+            ChunkerSpec::RabinKarp { width, base, .. } => {
+                check_width(width, &[32, 64, 128])?;
+                require(base % 2 == 1, || format!("the base {} must be odd", base))?;
+                require(width != 32 || base <= u64::from(u32::MAX), || {
+                    format!("the base {} doesn't fit into the width {}", base, width)
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the chunker supports the chunk sizes, so that building it doesn't panic.
+    pub fn validate(&self, sizes: &ChunkSizes) -> Result<(), SpecError> {
+        self.validate_parameters()?;
+        match *self {
+            ChunkerSpec::FixedSize
+            | ChunkerSpec::StadiaCdc
+            | ChunkerSpec::Ae
+            | ChunkerSpec::Ram
+            | ChunkerSpec::Lmc
+            | ChunkerSpec::SeqCdc { .. }
+            | ChunkerSpec::Bup => Ok(()),
+            ChunkerSpec::Borg => {
+                check_window(borg::WINDOW_SIZE, sizes)?;
+                check_mask_bits(sizes, 0, 32, MaskType::Simple)
+            }
+            ChunkerSpec::Casync => {
+                check_window(casync::WINDOW_SIZE, sizes)?;
+                require(casync::discriminator(sizes.avg_size()) > 0, || {
+                    format!("the avg size {} is too big for casync", sizes.avg_size())
+                })
+            }
+            ChunkerSpec::FastCdc2016 { nc } | ChunkerSpec::FastCdc2020 { nc } | ChunkerSpec::QuickCdc { nc } => {
+                check_window(1, sizes)?;
+                require((FAST_CDC_AVERAGE_MIN..=FAST_CDC_AVERAGE_MAX).contains(&sizes.avg_size()), || {
+                    format!(
+                        "the avg size {} is outside of [{};{}]",
+                        sizes.avg_size(),
+                        FAST_CDC_AVERAGE_MIN,
+                        FAST_CDC_AVERAGE_MAX
+                    )
+                })?;
+                let (low_bits, high_bits) = normalized_bits(sizes, nc)?;
+                require(low_bits >= 5 && high_bits <= 26, || {
+                    format!(
+                        "nc{} needs masks of {} to {} bits, but FastCDC has masks of 5 to 26 bits",
+                        nc, low_bits, high_bits
+                    )
+                })
+            }
+            ChunkerSpec::Pci { window, nc } => {
+                let window = match window {
+                    PciWindow::Bytes(window) => window,
+                    PciWindow::Min => sizes.min_size(),
+                };
+                check_window(window, sizes)?;
+                normalized_bits(sizes, nc).map(|_| ())
+            }
+            ChunkerSpec::Restic { .. } => {
+                check_window(restic::WINDOW_SIZE, sizes)?;
+                check_mask_bits(sizes, 0, 64, MaskType::Simple)
+            }
+            ChunkerSpec::Ronomon { nc, .. } => {
+                require(sizes.min_size() >= ronomon::MINIMUM_MIN, || {
+                    format!("the min size {} is smaller than {}", sizes.min_size(), ronomon::MINIMUM_MIN)
+                })?;
+                require((ronomon::AVERAGE_MIN..=ronomon::AVERAGE_MAX).contains(&sizes.avg_size()), || {
+                    format!(
+                        "the avg size {} is outside of [{};{}]",
+                        sizes.avg_size(),
+                        ronomon::AVERAGE_MIN,
+                        ronomon::AVERAGE_MAX
+                    )
+                })?;
+                let bits = logarithm2(sizes.avg_size() as u32);
+                require(bits >= nc + 5 && bits + nc <= 26, || {
+                    format!("nc{} with the avg size {} needs masks outside of 5 to 26 bits", nc, sizes.avg_size())
+                })
+            }
+            ChunkerSpec::BuzhashReg { window, .. } => {
+                check_window(window, sizes)?;
+                require(sizes.avg_size() <= u32::MAX as usize, || {
+                    format!("the avg size {} doesn't fit into 32 bits", sizes.avg_size())
+                })
+            }
+            ChunkerSpec::Buzhash { width, window, nc, mask, rc, .. } => {
+                check_window(window, sizes)?;
+                check_mask_bits(sizes, nc, width, mask)?;
+                check_regression_levels(sizes, rc)
+            }
+            ChunkerSpec::Gear { width, nc, rc, .. } => {
+                check_window(1, sizes)?;
+                check_mask_bits(sizes, nc, width, MaskType::Spread)?;
+                check_regression_levels(sizes, rc)
+            }
+            ChunkerSpec::Adler32 { window, nc, rc } => {
+                check_window(window, sizes)?;
+                check_mask_bits(sizes, nc, 32, MaskType::Simple)?;
+                check_regression_levels(sizes, rc)
+            }
+            ChunkerSpec::Polynomial { window, nc, rc, .. } => {
+                check_window(window, sizes)?;
+                check_mask_bits(sizes, nc, 64, MaskType::Simple)?;
+                check_regression_levels(sizes, rc)
+            }
+            ChunkerSpec::Tttd { hash, window } => {
+                check_window(window, sizes)?;
+                check_mask_bits(sizes, 0, hash.width(), MaskType::Simple)
+            }
+            ChunkerSpec::Crc { width, window, nc, rc } => {
+                check_window(window, sizes)?;
+                check_mask_bits(sizes, nc, width, MaskType::Simple)?;
+                check_regression_levels(sizes, rc)
+            }
+            ChunkerSpec::RabinKarp { width, window, nc, rc, .. } => {
+                check_window(window, sizes)?;
+                check_mask_bits(sizes, nc, width, MaskType::Spread)?;
+                check_regression_levels(sizes, rc)
+            }
+        }
+    }
+
+    /// Validates the parameters and builds the chunker.
+    pub fn build(&self, sizes: ChunkSizes) -> Result<Box<dyn Chunker>, SpecError> {
+        self.validate(&sizes)?;
+        let key = self.key().map(KeySource::read).transpose()?;
+        Ok(self.build_unchecked(sizes, key.as_deref()))
+    }
+
+    fn key(&self) -> Option<&KeySource> {
+        match self {
+            ChunkerSpec::Ronomon { key, .. }
+            | ChunkerSpec::Buzhash { key, .. }
+            | ChunkerSpec::Gear { key, .. }
+            | ChunkerSpec::Polynomial { key, .. } => key.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn build_unchecked(&self, sizes: ChunkSizes, key: Option<&[u8]>) -> Box<dyn Chunker> {
+        match *self {
+            ChunkerSpec::FixedSize => Box::new(Fixed::new()),
+            ChunkerSpec::Borg => Box::new(Borg::new(sizes)),
+            ChunkerSpec::Casync => Box::new(Casync::new(sizes)),
+            ChunkerSpec::FastCdc2016 { nc } => Box::new(FastCdc2016::new(sizes, nc)),
+            ChunkerSpec::FastCdc2020 { nc } => Box::new(FastCdc2020::new(sizes, nc)),
+            ChunkerSpec::StadiaCdc => Box::new(GoogleStadiaCdc::new(sizes)),
+            ChunkerSpec::Pci { window: PciWindow::Bytes(window), nc } => Box::new(Pci::new(sizes, window, nc)),
+            ChunkerSpec::Pci { window: PciWindow::Min, nc } => Box::new(Pci::new(sizes, sizes.min_size(), nc)),
+            ChunkerSpec::Restic { pol_seed } => {
+                Box::new(ResticCdc::new(Pol::generate_random_from_seed(pol_seed), sizes))
+            }
+            ChunkerSpec::Ronomon { width: 32, nc, .. } => match key {
+                None => Box::new(RonomonCdc::new_original(sizes, nc)),
+                Some(key) => Box::new(RonomonCdc::new_keyed::<u32>(key, sizes, nc)),
+            },
+            ChunkerSpec::Ronomon { width: 64, nc, .. } => match key {
+                None => Box::new(RonomonCdc::new_u64(sizes, nc)),
+                Some(key) => Box::new(RonomonCdc::new_keyed::<u64>(key, sizes, nc)),
+            },
+            ChunkerSpec::BuzhashReg { width: 32, window } => Box::new(Buzhash32Reg::new(sizes, window)),
+            ChunkerSpec::BuzhashReg { width: 64, window } => Box::new(Buzhash64Reg::new(sizes, window)),
+            ChunkerSpec::Buzhash { width: 32, table, window, nc, mask, rc, .. } => {
+                build_buz(sizes, select_table(table, key, sha256_u32_table), window, nc, mask, rc)
+            }
+            ChunkerSpec::Buzhash { width: 64, table, window, nc, mask, rc, .. } => {
+                build_buz(sizes, select_table(table, key, sha256_u64_table), window, nc, mask, rc)
+            }
+            ChunkerSpec::Buzhash { width: 128, table, window, nc, mask, rc, .. } => {
+                build_buz(sizes, select_table(table, key, sha256_u128_table), window, nc, mask, rc)
+            }
+            ChunkerSpec::Gear { width: 32, table, nc, rc, .. } => {
+                build_gear(sizes, select_table(table, key, sha256_u32_table), nc, rc)
+            }
+            ChunkerSpec::Gear { width: 64, table, nc, rc, .. } => {
+                build_gear(sizes, select_table(table, key, sha256_u64_table), nc, rc)
+            }
+            ChunkerSpec::Gear { width: 128, table, nc, rc, .. } => {
+                build_gear(sizes, select_table(table, key, sha256_u128_table), nc, rc)
+            }
+            ChunkerSpec::Adler32 { window, nc, rc } => {
+                Box::new(new_adler_u32(sizes, window, nc).with_regression(sizes, Box::new(create_simple_mask), rc))
+            }
+            ChunkerSpec::Polynomial { window, nc, pol_seed, rc, .. } => {
+                let pol = match key {
+                    Some(key) => Pol::generate_random_from_key(key),
+                    None => Pol::generate_random_from_seed(pol_seed),
+                };
+                Box::new(new_polynomial(sizes, pol, window, nc).with_regression(
+                    sizes,
+                    Box::new(create_simple_mask),
+                    rc,
+                ))
+            }
+            ChunkerSpec::Ae => Box::new(Ae::new(sizes)),
+            ChunkerSpec::Ram => Box::new(Ram::new(sizes)),
+            ChunkerSpec::Lmc => Box::new(Lmc::new(sizes)),
+            ChunkerSpec::Tttd { hash: RollingHashType::Buzhash, window } => Box::new(Tttd::new(
+                sizes,
+                BuzHashBuilder::new(sha256_u64_table(), window),
+                Box::new(create_simple_mask),
+            )),
+            ChunkerSpec::Tttd { hash: RollingHashType::Polynomial, window } => Box::new(Tttd::new(
+                sizes,
+                PolynomialHashBuilder::new(Pol::generate_random_from_seed(DEFAULT_POL_SEED), window),
+                Box::new(create_simple_mask),
+            )),
+            ChunkerSpec::Tttd { hash: RollingHashType::Adler32, window } => {
+                Box::new(Tttd::new(sizes, Adler32Builder::new(window), Box::new(create_simple_mask)))
+            }
+            ChunkerSpec::SeqCdc { order } => Box::new(SeqCdc::new(sizes, order)),
+            ChunkerSpec::QuickCdc { nc } => Box::new(QuickCdc::new(FastCdc2020::new(sizes, nc))),
+            ChunkerSpec::Crc { width: 32, window, nc, rc } => {
+                Box::new(new_crc32c(sizes, window, nc).with_regression(sizes, Box::new(create_simple_mask), rc))
+            }
+            ChunkerSpec::Crc { width: 64, window, nc, rc } => {
+                Box::new(new_crc64_xz(sizes, window, nc).with_regression(sizes, Box::new(create_simple_mask), rc))
+            }
+            ChunkerSpec::Bup => Box::new(Bup::new(sizes)),
+            ChunkerSpec::RabinKarp { width: 32, window, base, nc, rc } => {
+                build_rabin_karp(sizes, u32::try_from(base).expect("The base is validated"), window, nc, rc)
+            }
+            ChunkerSpec::RabinKarp { width: 64, window, base, nc, rc } => build_rabin_karp(sizes, base, window, nc, rc),
+            ChunkerSpec::RabinKarp { width: 128, window, base, nc, rc } => {
+                build_rabin_karp(sizes, base as u128, window, nc, rc)
+            }
+            ref spec => unreachable!("The width of {:?} is validated", spec),
+        }
+    }
+}
+
+fn require(condition: bool, message: impl FnOnce() -> String) -> Result<(), SpecError> {
+    if condition {
+        Ok(())
+    } else {
+        Err(SpecError(message()))
+    }
+}
+
+fn check_width(width: u32, supported: &[u32]) -> Result<(), SpecError> {
+    require(supported.contains(&width), || format!("the width {} is not one of {:?}", width, supported))
+}
+
+/// The hash is prepared from the `window` bytes before the min size.
+fn check_window(window: usize, sizes: &ChunkSizes) -> Result<(), SpecError> {
+    require(window > 0, || "the window must not be empty".to_string())?;
+    require(window <= sizes.min_size(), || {
+        format!("the window of {} bytes is bigger than the min size {}", window, sizes.min_size())
+    })
+}
+
+/// The number of mask bits before and after the avg size for the normalization level.
+fn normalized_bits(sizes: &ChunkSizes, nc: u32) -> Result<(u32, u32), SpecError> {
+    let low_target = sizes.avg_size().checked_shr(nc).unwrap_or(0);
+    let high_target =
+        (sizes.avg_size() as u64).checked_shl(nc).filter(|target| target >> nc == sizes.avg_size() as u64);
+    match high_target {
+        Some(high_target) if low_target > 0 && high_target <= u32::MAX as u64 => {
+            Ok((logarithm2(low_target as u32), logarithm2(high_target as u32)))
+        }
+        _ => Err(SpecError(format!("nc{} is too big for the avg size {}", nc, sizes.avg_size()))),
+    }
+}
+
+/// The regression masks are for the halves of the avg size, so every level needs a bit of the avg size mask.
+fn check_regression_levels(sizes: &ChunkSizes, rc: u32) -> Result<(), SpecError> {
+    let bits = logarithm2(sizes.avg_size() as u32);
+    require(rc < bits, || format!("rc{} needs more than the {} bits of the avg size mask", rc, bits))
+}
+
+/// Checks that both normalized masks fit into the digest.
+fn check_mask_bits(sizes: &ChunkSizes, nc: u32, width: u32, mask: MaskType) -> Result<(), SpecError> {
+    let (_, high_bits) = normalized_bits(sizes, nc)?;
+    let fits = match mask {
+        MaskType::Simple => high_bits < width,
+        MaskType::Spread => high_bits <= width,
+    };
+    require(fits, || format!("the mask of {} bits doesn't fit into the {} bit digest", high_bits, width))
+}
+
+fn select_table<T: UnsignedInteger>(table: HashTable, key: Option<&[u8]>, sha256_table: fn() -> [T; 256]) -> [T; 256] {
+    match (table, key) {
+        (HashTable::Sha256, _) => sha256_table(),
+        (HashTable::Buz, None) => buz_table(),
+        (HashTable::Buz, Some(key)) => keyed_table(key),
+    }
+}
+
+fn build_buz<T: UnsignedInteger>(
+    sizes: ChunkSizes,
+    table: [T; 256],
+    window: usize,
+    nc: u32,
+    mask: MaskType,
+    rc: u32,
+) -> Box<dyn Chunker> {
+    match mask {
+        MaskType::Simple => {
+            Box::new(new_buz(sizes, table, window, nc).with_regression(sizes, Box::new(create_simple_mask), rc))
+        }
+        MaskType::Spread => Box::new(new_buz_spread_mask(sizes, table, window, nc).with_regression(
+            sizes,
+            Box::new(create_spread_mask),
+            rc,
+        )),
+    }
+}
+
+fn build_rabin_karp<T: UnsignedInteger>(
+    sizes: ChunkSizes,
+    base: T,
+    window: usize,
+    nc: u32,
+    rc: u32,
+) -> Box<dyn Chunker> {
+    Box::new(new_rabin_karp(sizes, base, window, nc).with_regression(sizes, Box::new(create_spread_mask), rc))
+}
+
+fn build_gear<T: UnsignedInteger>(sizes: ChunkSizes, table: [T; 256], nc: u32, rc: u32) -> Box<dyn Chunker> {
+    Box::new(new_gear_spread_mask(sizes, table, nc).with_regression(sizes, Box::new(create_spread_mask), rc))
+}
+
+/// The `key=value` parameters of a spec string.
+/// Every parameter is taken once, and the ones left over are reported as unknown.
+struct Params<'a> {
+    family: &'a str,
+    values: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Params<'a> {
+    fn parse(family: &'a str, params: &'a str) -> Result<Self, SpecError> {
+        let mut values: Vec<(&str, &str)> = Vec::new();
+        for param in params.split(',').map(str::trim).filter(|param| !param.is_empty()) {
+            let (key, value) = param
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| SpecError(format!("expected 'key=value', got '{}'", param)))?;
+            require(!values.iter().any(|(existing, _)| *existing == key), || {
+                format!("the parameter '{}' is repeated", key)
+            })?;
+            values.push((key, value));
+        }
+        Ok(Self { family, values })
+    }
+
+    fn take<T: FromStr>(&mut self, key: &str, default: Option<T>) -> Result<T, SpecError> {
+        match self.values.iter().position(|(existing, _)| *existing == key) {
+            Some(index) => {
+                let (_, value) = self.values.remove(index);
+                value.parse().map_err(|_| SpecError(format!("invalid value '{}' of '{}'", value, key)))
+            }
+            None => default.ok_or_else(|| SpecError(format!("{} requires the '{}' parameter", self.family, key))),
+        }
+    }
+
+    fn required<T: FromStr>(&mut self, key: &str) -> Result<T, SpecError> {
+        self.take(key, None)
+    }
+
+    fn optional<T: FromStr>(&mut self, key: &str, default: T) -> Result<T, SpecError> {
+        self.take(key, Some(default))
+    }
+
+    /// A parameter without a default value, which is `None` when it is not given.
+    fn maybe<T: FromStr>(&mut self, key: &str) -> Result<Option<T>, SpecError> {
+        if self.values.iter().any(|(existing, _)| *existing == key) {
+            self.required(key).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn finish(self, spec: ChunkerSpec) -> Result<ChunkerSpec, SpecError> {
+        match self.values.first() {
+            Some((key, _)) => Err(SpecError(format!("{} has no '{}' parameter", self.family, key))),
+            None => spec.validate_parameters().map(|_| spec),
+        }
+    }
+}
+
+impl FromStr for ChunkerSpec {
+    type Err = SpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (family, params) = s.split_once(':').unwrap_or((s, ""));
+        let family = family.trim();
+        let mut params = Params::parse(family, params)?;
+        let spec = match family {
+            "fixed" => ChunkerSpec::FixedSize,
+            "borg" => ChunkerSpec::Borg,
+            "casync" => ChunkerSpec::Casync,
+            "fastcdc2016" => ChunkerSpec::FastCdc2016 { nc: params.optional("nc", FAST_CDC_DEFAULT_NC)? },
+            "fastcdc2020" => ChunkerSpec::FastCdc2020 { nc: params.optional("nc", FAST_CDC_DEFAULT_NC)? },
+            "stadia" => ChunkerSpec::StadiaCdc,
+            "pci" => ChunkerSpec::Pci { window: params.required("window")?, nc: params.optional("nc", 0)? },
+            "restic" => ChunkerSpec::Restic { pol_seed: params.optional("pol_seed", DEFAULT_POL_SEED)? },
+            "ronomon" => ChunkerSpec::Ronomon {
+                width: params.optional("width", 32)?,
+                nc: params.optional("nc", RONOMON_DEFAULT_NC)?,
+                key: params.maybe("key")?,
+            },
+            "buzhash_reg" => {
+                ChunkerSpec::BuzhashReg { width: params.required("width")?, window: params.required("window")? }
+            }
+            "buzhash" => ChunkerSpec::Buzhash {
+                width: params.required("width")?,
+                table: params.optional("table", HashTable::Sha256)?,
+                window: params.required("window")?,
+                nc: params.optional("nc", 0)?,
+                mask: params.optional("mask", MaskType::Simple)?,
+                rc: params.optional("rc", 0)?,
+                key: params.maybe("key")?,
+            },
+            "gear" => ChunkerSpec::Gear {
+                width: params.required("width")?,
+                table: params.optional("table", HashTable::Sha256)?,
+                nc: params.optional("nc", 0)?,
+                rc: params.optional("rc", 0)?,
+                key: params.maybe("key")?,
+            },
+            "adler32" => ChunkerSpec::Adler32 {
+                window: params.required("window")?,
+                nc: params.optional("nc", 0)?,
+                rc: params.optional("rc", 0)?,
+            },
+            "polynomial" => ChunkerSpec::Polynomial {
+                window: params.required("window")?,
+                nc: params.optional("nc", 0)?,
+                pol_seed: params.optional("pol_seed", DEFAULT_POL_SEED)?,
+                rc: params.optional("rc", 0)?,
+                key: params.maybe("key")?,
+            },
+            "ae" => ChunkerSpec::Ae,
+            "ram" => ChunkerSpec::Ram,
+            "lmc" => ChunkerSpec::Lmc,
+            "tttd" => ChunkerSpec::Tttd { hash: params.required("hash")?, window: params.required("window")? },
+            "seqcdc" => ChunkerSpec::SeqCdc { order: params.required("order")? },
+            "quickcdc" => ChunkerSpec::QuickCdc { nc: params.optional("nc", FAST_CDC_DEFAULT_NC)? },
+            "bup" => ChunkerSpec::Bup,
+            "crc" => ChunkerSpec::Crc {
+                width: params.required("width")?,
+                window: params.required("window")?,
+                nc: params.optional("nc", 0)?,
+                rc: params.optional("rc", 0)?,
+            },
+            "rabin_karp" => {
+                let width = params.required("width")?;
+                ChunkerSpec::RabinKarp {
+                    width,
+                    window: params.required("window")?,
+                    base: params.optional("base", default_rabin_karp_base(width))?,
+                    nc: params.optional("nc", 0)?,
+                    rc: params.optional("rc", 0)?,
+                }
+            }
+            _ => return Err(SpecError(format!("unknown chunker family '{}'", family))),
+        };
+        params.finish(spec)
+    }
+}
+
+impl<'de> Deserialize<'de> for ChunkerSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Formats the normalization level, omitting the default one.
+fn nc_suffix(nc: u32, default_nc: u32) -> String {
+    if nc == default_nc {
+        String::new()
+    } else {
+        format!(" nc{}", nc)
+    }
+}
+
+/// Formats the regression levels, omitting the chunking without regression.
+fn rc_suffix(rc: u32) -> String {
+    if rc == 0 {
+        String::new()
+    } else {
+        format!(" RC{}", rc)
+    }
+}
+
+/// The FNV prime of the width, which fits into the hash.
+fn default_rabin_karp_base(width: u32) -> u64 {
+    if width == 32 {
+        DEFAULT_RABIN_KARP_BASE_32
+    } else {
+        DEFAULT_RABIN_KARP_BASE_64
+    }
+}
+
+fn base_suffix(width: u32, base: u64) -> String {
+    if base == default_rabin_karp_base(width) {
+        String::new()
+    } else {
+        format!(" base{}", base)
+    }
+}
+
+/// The key is a parameter without a default, so it is only in the spec string when it is given.
+fn key_param(key: &Option<KeySource>) -> String {
+    key.as_ref().map(|key| format!(",key={}", key)).unwrap_or_default()
+}
+
+/// Only the source of the key is in the name, which tells apart the chunkers with different keys.
+fn key_suffix(key: &Option<KeySource>) -> String {
+    key.as_ref().map(|key| format!(" keyed {}", key)).unwrap_or_default()
+}
+
+fn seed_suffix(pol_seed: u64) -> String {
+    if pol_seed == DEFAULT_POL_SEED {
+        String::new()
+    } else {
+        format!(" seed{}", pol_seed)
+    }
+}
+
+/// The canonical name of the chunker in the reports.
+impl Display for ChunkerSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ChunkerSpec::FixedSize => write!(f, "FixedSize"),
+            ChunkerSpec::Borg => write!(f, "Borg"),
+            ChunkerSpec::Casync => write!(f, "Casync"),
+            ChunkerSpec::FastCdc2016 { nc } => write!(f, "FastCdc2016{}", nc_suffix(nc, FAST_CDC_DEFAULT_NC)),
+            ChunkerSpec::FastCdc2020 { nc } => write!(f, "FastCdc2020{}", nc_suffix(nc, FAST_CDC_DEFAULT_NC)),
+            ChunkerSpec::StadiaCdc => write!(f, "StadiaCdc"),
+            ChunkerSpec::Pci { window: PciWindow::Bytes(window), nc } => {
+                write!(f, "Pci {}{}", window, nc_suffix(nc, 0))
+            }
+            ChunkerSpec::Pci { window: PciWindow::Min, nc } => write!(f, "Pci min{}", nc_suffix(nc, 0)),
+            ChunkerSpec::Restic { pol_seed } => write!(f, "Restic{}", seed_suffix(pol_seed)),
+            ChunkerSpec::Ronomon { width, nc, ref key } => {
+                let width = if width == 32 { String::new() } else { width.to_string() };
+                write!(f, "Ronomon{}{}{}", width, nc_suffix(nc, RONOMON_DEFAULT_NC), key_suffix(key))
+            }
+            ChunkerSpec::BuzhashReg { width, window } => write!(f, "Buzhash{}Reg {}", width, window),
+            ChunkerSpec::Buzhash { width, table, window, nc, mask, rc, ref key } => {
+                let table = if table == HashTable::Buz { "b" } else { "" };
+                let mask = if mask == MaskType::Spread { " spread" } else { "" };
+                let (nc, rc, key) = (nc_suffix(nc, 0), rc_suffix(rc), key_suffix(key));
+                write!(f, "Buzhash{}{} {}{}{}{}{}", width, table, window, mask, nc, rc, key)
+            }
+            ChunkerSpec::Gear { width, table, nc, rc, ref key } => {
+                let table = if table == HashTable::Buz { " Buz table" } else { "" };
+                write!(f, "Gear{}{}{}{}{}", width, nc_suffix(nc, 0), rc_suffix(rc), table, key_suffix(key))
+            }
+            ChunkerSpec::Adler32 { window, nc, rc } => {
+                write!(f, "Adler32 {}{}{}", window, nc_suffix(nc, 0), rc_suffix(rc))
+            }
+            ChunkerSpec::Polynomial { window, nc, pol_seed, rc, ref key } => {
+                let (nc, seed, rc, key) = (nc_suffix(nc, 0), seed_suffix(pol_seed), rc_suffix(rc), key_suffix(key));
+                write!(f, "Polynomial {}{}{}{}{}", window, nc, seed, rc, key)
+            }
+            ChunkerSpec::Ae => write!(f, "Ae"),
+            ChunkerSpec::Ram => write!(f, "Ram"),
+            ChunkerSpec::Lmc => write!(f, "Lmc"),
+            ChunkerSpec::Tttd { hash, window } => {
+                let hash = match hash {
+                    RollingHashType::Buzhash => "Buzhash64",
+                    RollingHashType::Polynomial => "Polynomial",
+                    RollingHashType::Adler32 => "Adler32",
+                };
+                write!(f, "Tttd {} {}", hash, window)
+            }
+            ChunkerSpec::SeqCdc { order: SeqOrder::Increasing } => write!(f, "SeqCdc increasing"),
+            ChunkerSpec::SeqCdc { order: SeqOrder::Decreasing } => write!(f, "SeqCdc decreasing"),
+            ChunkerSpec::QuickCdc { nc } => write!(f, "QuickCdc{}", nc_suffix(nc, FAST_CDC_DEFAULT_NC)),
+            ChunkerSpec::Bup => write!(f, "Bup"),
+            ChunkerSpec::Crc { width, window, nc, rc } => {
+                write!(f, "Crc{} {}{}{}", width, window, nc_suffix(nc, 0), rc_suffix(rc))
+            }
+            ChunkerSpec::RabinKarp { width, window, base, nc, rc } => {
+                write!(
+                    f,
+                    "RabinKarp{} {}{}{}{}",
+                    width,
+                    window,
+                    nc_suffix(nc, 0),
+                    base_suffix(width, base),
+                    rc_suffix(rc)
+                )
+            }
+        }
+    }
+}
+
+/// The key of the keyed spec in [EXAMPLE_SPECS].
+#[cfg(test)]
+pub(crate) fn set_test_key() {
+    static SET_KEY: std::sync::Once = std::sync::Once::new();
+    SET_KEY.call_once(|| std::env::set_var("CDC_TEST_KEY", "000102030405060708090a0b0c0d0e0f"));
+}
+
+/// A spec of every family, in the order of [FAMILIES]. The tests run every family through these specs.
+#[cfg(test)]
+pub(crate) const EXAMPLE_SPECS: [&str; 23] = [
+    "fixed",
+    "borg",
+    "casync",
+    "fastcdc2016:nc=3",
+    "fastcdc2020",
+    "stadia",
+    "pci:window=min,nc=1",
+    "restic:pol_seed=7",
+    "ronomon:width=64,nc=2",
+    "buzhash_reg:width=32,window=64",
+    "buzhash:width=128,table=buz,window=256,nc=1,mask=spread,key=env:CDC_TEST_KEY",
+    "gear:width=64,nc=1",
+    "adler32:window=4096,rc=4",
+    "polynomial:window=64,pol_seed=3",
+    "ae",
+    "ram",
+    "lmc",
+    "tttd:hash=polynomial,window=48",
+    "seqcdc:order=decreasing",
+    "quickcdc:nc=1",
+    "bup",
+    "crc:width=32,window=64,nc=1",
+    "rabin_karp:width=64,window=64,base=257",
+];
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::registry::{
+        expand_specs, set_test_key, ChunkerSpec, HashTable, MaskType, EXAMPLE_SPECS, FAMILIES,
+    };
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::slice_chunks::SliceChunks;
+    use crate::util::{KB, MB};
+
+    #[test]
+    fn should_parse_spec_strings() {
+        let spec: ChunkerSpec = "buzhash:width=64,table=buz,window=256,nc=1,mask=spread".parse().unwrap();
+        assert_eq!(
+            spec,
+            ChunkerSpec::Buzhash {
+                width: 64,
+                table: HashTable::Buz,
+                window: 256,
+                nc: 1,
+                mask: MaskType::Spread,
+                rc: 0,
+                key: None
+            }
+        );
+        assert_eq!(spec.to_string(), "Buzhash64b 256 spread nc1");
+        assert_eq!("fastcdc2020:nc=2".parse::<ChunkerSpec>().unwrap(), ChunkerSpec::FastCdc2020 { nc: 2 });
+        assert_eq!("restic:pol_seed=7".parse::<ChunkerSpec>().unwrap().to_string(), "Restic seed7");
+        assert_eq!("ronomon".parse::<ChunkerSpec>().unwrap().spec_string(), "ronomon:width=32,nc=1");
+    }
+
+    #[test]
+    fn should_expand_the_parameter_lists() {
+        let names = expand_specs("buzhash:width=32|64,window=64|256,mask=spread")
+            .unwrap()
+            .into_iter()
+            .map(|spec| spec.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["Buzhash32 64 spread", "Buzhash32 256 spread", "Buzhash64 64 spread", "Buzhash64 256 spread"]
+        );
+        assert_eq!(expand_specs("bup").unwrap(), vec![ChunkerSpec::Bup]);
+        assert_eq!(
+            expand_specs("gear:width=16|64").unwrap_err().to_string(),
+            "the width 16 is not one of [32, 64, 128]"
+        );
+    }
+
+    #[test]
+    fn should_round_trip_spec_strings() {
+        set_test_key();
+        let specs = EXAMPLE_SPECS.map(|spec| spec.parse::<ChunkerSpec>().unwrap());
+        assert_eq!(specs.each_ref().map(ChunkerSpec::family), FAMILIES.map(|(family, _)| family));
+        for spec in specs {
+            assert_eq!(spec.spec_string().parse::<ChunkerSpec>().unwrap(), spec);
+            assert!(spec.build(ChunkSizes::new(64 * KB, 128 * KB, 256 * KB)).is_ok(), "{}", spec.spec_string());
+        }
+    }
+
+    #[test]
+    fn should_report_invalid_specs() {
+        let errors = [
+            ("unknown", "unknown chunker family 'unknown'"),
+            ("gear:nc=1", "gear requires the 'width' parameter"),
+            ("fastcdc2016:nc=1,window=64", "fastcdc2016 has no 'window' parameter"),
+            ("adler32:window=64,window=32", "the parameter 'window' is repeated"),
+            ("buzhash:width=32,window=64,mask=dense", "invalid value 'dense' of 'mask'"),
+            ("pci:window", "expected 'key=value', got 'window'"),
+            ("gear:width=16", "the width 16 is not one of [32, 64, 128]"),
+            ("gear:width=64,key=env:KEY", "the key derives the balanced table, so it requires the buz table"),
+            ("gear:width=64,table=buz,key=42", "invalid value '42' of 'key'"),
+            ("rabin_karp:width=32,window=64,base=4294967297", "the base 4294967297 doesn't fit into the width 32"),
+        ];
+        for (spec, error) in errors {
+            assert_eq!(spec.parse::<ChunkerSpec>().unwrap_err().to_string(), error);
+        }
+    }
+
+    #[test]
+    fn should_report_unsupported_sizes() {
+        let sizes = ChunkSizes::new(32, 32 * MB, 64 * MB);
+        let build = |spec: &str| spec.parse::<ChunkerSpec>().unwrap().build(sizes);
+        assert!(build("ronomon").is_err());
+        assert!(build("fastcdc2020").is_err());
+        assert!(build("buzhash:width=32,window=64").is_err());
+        assert!(build("fixed").is_ok());
+    }
+
+    #[test]
+    fn should_keep_the_dedup_ratio_with_keyed_tables() {
+        set_test_key();
+        let mut first = vec![0u8; 8 * MB];
+        ChaCha20Rng::seed_from_u64(21).fill(first.as_mut_slice());
+        let mut second = first.clone();
+        for offset in (0..second.len()).step_by(256 * KB) {
+            second.insert(offset, 0);
+        }
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 64 * KB);
+        let dedup_ratio = |spec: &str| {
+            let chunker = spec.parse::<ChunkerSpec>().unwrap().build(sizes).unwrap();
+            let chunks = [&first, &second].into_iter().flat_map(|data| {
+                SliceChunks::new(data, chunker.as_ref(), sizes)
+                    .map(|chunk| &data[chunk.offset..chunk.offset + chunk.length])
+            });
+            let unique_size = chunks.collect::<HashSet<_>>().into_iter().map(<[u8]>::len).sum::<usize>();
+            (first.len() + second.len()) as f64 / unique_size as f64
+        };
+
+        for (unkeyed, keyed) in [
+            ("gear:width=64,table=buz", "gear:width=64,table=buz,key=env:CDC_TEST_KEY"),
+            ("buzhash:width=64,table=buz,window=64", "buzhash:width=64,table=buz,window=64,key=env:CDC_TEST_KEY"),
+            ("polynomial:window=64", "polynomial:window=64,key=env:CDC_TEST_KEY"),
+            ("ronomon:width=64", "ronomon:width=64,key=env:CDC_TEST_KEY"),
+        ] {
+            let (unkeyed_ratio, keyed_ratio) = (dedup_ratio(unkeyed), dedup_ratio(keyed));
+            assert!(unkeyed_ratio > 1.5, "{} {}", unkeyed, unkeyed_ratio);
+            assert!((unkeyed_ratio - keyed_ratio).abs() < 0.05, "{} {} {}", keyed, unkeyed_ratio, keyed_ratio);
+        }
+    }
+
+    #[test]
+    fn should_read_the_key_from_its_source() {
+        set_test_key();
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 64 * KB);
+        let mut data = vec![0u8; MB];
+        ChaCha20Rng::seed_from_u64(21).fill(data.as_mut_slice());
+        let boundaries = |spec: &ChunkerSpec| {
+            let chunker = spec.build(sizes).unwrap();
+            SliceChunks::new(&data, chunker.as_ref(), sizes).map(|chunk| chunk.length).collect::<Vec<_>>()
+        };
+
+        let from_env: ChunkerSpec = "gear:width=64,table=buz,key=env:CDC_TEST_KEY".parse().unwrap();
+        assert_eq!(from_env.to_string(), "Gear64 Buz table keyed env:CDC_TEST_KEY");
+        assert_eq!(from_env.spec_string(), "gear:width=64,table=buz,nc=0,rc=0,key=env:CDC_TEST_KEY");
+
+        let path = std::env::temp_dir().join("cdc-comparison-test.key");
+        std::fs::write(&path, (0..16).collect::<Vec<u8>>()).unwrap();
+        let from_file: ChunkerSpec = format!("gear:width=64,table=buz,key=file:{}", path.display()).parse().unwrap();
+        assert_eq!(boundaries(&from_file), boundaries(&from_env));
+        assert!(boundaries(&from_env) != boundaries(&"gear:width=64,table=buz".parse().unwrap()));
+
+        std::fs::write(&path, [0; 8]).unwrap();
+        assert_eq!(
+            from_file.build(sizes).err().unwrap().to_string(),
+            format!("the key of file:{} is shorter than 16 bytes", path.display())
+        );
+        let missing: ChunkerSpec = "gear:width=64,table=buz,key=env:CDC_MISSING_TEST_KEY".parse().unwrap();
+        assert_eq!(missing.build(sizes).err().unwrap().to_string(), "the key variable CDC_MISSING_TEST_KEY is not set");
+    }
+}
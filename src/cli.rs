@@ -0,0 +1,267 @@
+use std::path::PathBuf;
+
+use cdc_comparison::util::parse_size;
+use cdc_comparison::ChunkerSpec;
+use clap::{Args, Parser, Subcommand};
+use regex::Regex;
+
+use crate::benchmark::resilience::ShiftEdit;
+use crate::benchmark::synthetic::Content;
+use crate::benchmark::{InputMode, SizePreset, StreamMode};
+
+/// Compares content defined chunking algorithms.
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Chunks the inputs with every selected chunker and writes a json report per run.
+    Bench(BenchArgs),
+    /// Evaluates the chunkers, sizes and datasets described by an experiment file.
+    Experiment(ExperimentArgs),
+    /// Measures how fast the chunk stream splits the inputs, without the deduplication.
+    Throughput(ThroughputArgs),
+    /// Deduplicates the inputs by whole files, which is the baseline for the chunkers.
+    FullFiles(FullFilesArgs),
+    /// Merges the per run reports into the per average size reports.
+    Merge(MergeArgs),
+    /// Prints the names of the selected chunkers.
+    ListChunkers(ListChunkersArgs),
+    /// Prints the bit balance, the bit correlation, the avalanche and the split probability of the lookup tables.
+    AnalyzeTables(AnalyzeTablesArgs),
+    /// Compares the boundary rates of the chunkers on random data with the rates that their masks assume.
+    Calibrate(CalibrateArgs),
+    /// Writes a reproducible synthetic input and its edited versions, which are the inputs of a benchmark.
+    Generate(GenerateArgs),
+    /// Measures how many chunks change after a single insertion or deletion before the boundaries resynchronize.
+    BoundaryShift(BoundaryShiftArgs),
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Files or directories to chunk. The files of a directory are read in the name order without file boundaries.
+    #[arg(short, long = "input", num_args = 1.., default_values = ["data/concatenated/postgres-15.2.tar", "data/concatenated/postgres-15.3.tar"])]
+    pub inputs: Vec<PathBuf>,
+    /// Directory for the json reports.
+    #[arg(short, long, default_value = "results/json")]
+    pub output: PathBuf,
+    /// Average chunk sizes, e.g. `64KB,1MB`.
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_size, default_value = "64KB,128KB,256KB,512KB,1MB,2MB")]
+    pub avg_sizes: Vec<usize>,
+    /// Min/avg/max combinations evaluated for every average size.
+    #[arg(short, long, value_enum, default_value_t = SizePreset::Standard)]
+    pub preset: SizePreset,
+    /// Chunkers as spec strings, e.g. `buzhash:width=64,window=256,nc=1`, instead of the default ones.
+    #[arg(short, long = "chunker")]
+    pub chunkers: Vec<ChunkerSpec>,
+    /// The way the inputs are read. The mapped and the loaded inputs are shared by all the runs.
+    #[arg(long, value_enum, default_value_t = InputMode::Memory)]
+    pub input_mode: InputMode,
+    /// The total size of the inputs that are loaded into memory in the `memory` input mode.
+    #[arg(long, value_parser = parse_size, default_value = "4096MB")]
+    pub memory_budget: usize,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(Args)]
+pub struct ExperimentArgs {
+    /// TOML or JSON experiment file, see `experiments/postgres.toml`.
+    pub file: PathBuf,
+    /// The total size of the inputs that are loaded into memory in the `memory` input mode.
+    #[arg(long, value_parser = parse_size, default_value = "4096MB")]
+    pub memory_budget: usize,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(Args)]
+pub struct ThroughputArgs {
+    /// Files or directories to chunk. The files of a directory are read in the name order without file boundaries.
+    #[arg(short, long = "input", num_args = 1.., default_values = ["data/concatenated/postgres-15.2.tar", "data/concatenated/postgres-15.3.tar"])]
+    pub inputs: Vec<PathBuf>,
+    /// Average chunk sizes, e.g. `64KB,1MB`.
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_size, default_value = "64KB")]
+    pub avg_sizes: Vec<usize>,
+    /// Min/avg/max combinations evaluated for every average size.
+    #[arg(short, long, value_enum, default_value_t = SizePreset::Restic)]
+    pub preset: SizePreset,
+    /// Chunkers as spec strings.
+    #[arg(short, long = "chunker", default_values = ["fixed", "fastcdc2020", "gear:width=64"])]
+    pub chunkers: Vec<ChunkerSpec>,
+    /// The ways to take the chunks from the stream.
+    #[arg(short, long = "mode", value_enum, value_delimiter = ',', default_value = "borrowed,owned")]
+    pub modes: Vec<StreamMode>,
+}
+
+#[derive(Args)]
+pub struct FullFilesArgs {
+    /// Directories with the extracted files.
+    #[arg(short, long = "input", num_args = 1.., default_values = ["data/extracted/postgres-15.2-extracted", "data/extracted/postgres-15.3-extracted"])]
+    pub inputs: Vec<PathBuf>,
+    /// Directory for the report.
+    #[arg(short, long, default_value = "results")]
+    pub output: PathBuf,
+}
+
+#[derive(Args)]
+pub struct MergeArgs {
+    /// Directory with the json reports.
+    #[arg(short, long, default_value = "results/json")]
+    pub output: PathBuf,
+}
+
+#[derive(Args)]
+pub struct ListChunkersArgs {
+    /// Lists the chunkers of the experiment file instead of the default ones.
+    #[arg(long)]
+    pub experiment: Option<PathBuf>,
+    /// Prints the spec string of every chunker after its name.
+    #[arg(long)]
+    pub specs: bool,
+    /// Prints the chunker families with their parameters instead of the chunkers.
+    #[arg(long)]
+    pub families: bool,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(Args)]
+pub struct AnalyzeTablesArgs {
+    /// The window of the Buzhash tables that are not tied to a chunker.
+    #[arg(short, long, default_value_t = 64)]
+    pub window: usize,
+    /// The number of the mask bits for the split probability.
+    #[arg(short, long, default_value_t = 12)]
+    pub mask_bits: u32,
+    /// The number of the single bit flips for the avalanche.
+    #[arg(short, long, default_value_t = 100_000)]
+    pub samples: usize,
+    /// The size of the random input for the split probability.
+    #[arg(short, long, value_parser = parse_size, default_value = "256MB")]
+    pub bytes: usize,
+}
+
+#[derive(Args)]
+pub struct CalibrateArgs {
+    /// Average chunk sizes, e.g. `64KB,1MB`.
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_size, default_value = "64KB,1MB")]
+    pub avg_sizes: Vec<usize>,
+    /// Min/avg/max combinations evaluated for every average size.
+    #[arg(short, long, value_enum, default_value_t = SizePreset::Double)]
+    pub preset: SizePreset,
+    /// Chunkers as spec strings instead of the default ones.
+    #[arg(short, long = "chunker")]
+    pub chunkers: Vec<ChunkerSpec>,
+    /// The size of the random data.
+    #[arg(short, long, value_parser = parse_size, default_value = "256MB")]
+    pub bytes: usize,
+    /// The relative difference between the average chunk size and the avg size above which a chunker is flagged.
+    #[arg(short, long, default_value_t = 0.1)]
+    pub tolerance: f64,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    /// Directory for the `version-<n>.bin` files.
+    #[arg(short, long, default_value = "data/synthetic")]
+    pub output: PathBuf,
+    /// The kind of the generated and the inserted bytes.
+    #[arg(short, long, value_enum, default_value_t = Content::Random)]
+    pub content: Content,
+    /// The seed of the ChaCha generator.
+    #[arg(short, long, default_value_t = 0)]
+    pub seed: u64,
+    /// The size of the first version.
+    #[arg(long, value_parser = parse_size, default_value = "256MB")]
+    pub size: usize,
+    /// The number of versions, including the first one. Every version is an edit of the previous one.
+    #[arg(short, long, default_value_t = 2)]
+    pub versions: usize,
+    /// Insertions per MB of the previous version.
+    #[arg(long, default_value_t = 1.0)]
+    pub insertions: f64,
+    /// Deletions per MB of the previous version.
+    #[arg(long, default_value_t = 1.0)]
+    pub deletions: f64,
+    /// Overwrites per MB of the previous version.
+    #[arg(long, default_value_t = 1.0)]
+    pub overwrites: f64,
+    /// Moves of a range to another position per MB of the previous version.
+    #[arg(long, default_value_t = 0.25)]
+    pub moves: f64,
+    /// The edits are from one byte up to this size.
+    #[arg(long, value_parser = parse_size, default_value = "4KB")]
+    pub max_edit_size: usize,
+}
+
+#[derive(Args)]
+pub struct BoundaryShiftArgs {
+    /// The base input. A synthetic input is generated when it is not set.
+    #[arg(short, long)]
+    pub input: Option<PathBuf>,
+    /// The kind of the generated base input and of the inserted bytes.
+    #[arg(long, value_enum, default_value_t = Content::Random)]
+    pub content: Content,
+    /// The seed of the ChaCha generators of the base input, the inserted bytes and the edit positions.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// The size of the generated base input.
+    #[arg(long, value_parser = parse_size, default_value = "64MB")]
+    pub size: usize,
+    /// Average chunk sizes, e.g. `64KB,1MB`.
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_size, default_value = "64KB")]
+    pub avg_sizes: Vec<usize>,
+    /// Min/avg/max combinations evaluated for every average size.
+    #[arg(short, long, value_enum, default_value_t = SizePreset::Double)]
+    pub preset: SizePreset,
+    /// Chunkers as spec strings instead of the default ones.
+    #[arg(short, long = "chunker")]
+    pub chunkers: Vec<ChunkerSpec>,
+    /// The kinds of the edits.
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "insert,delete")]
+    pub edits: Vec<ShiftEdit>,
+    /// The lengths of the inserted or deleted bytes.
+    #[arg(long, value_delimiter = ',', value_parser = parse_size, default_value = "1,64,4KB")]
+    pub edit_sizes: Vec<usize>,
+    /// The number of the random edit positions for every chunker, edit and length.
+    #[arg(short, long, default_value_t = 1000)]
+    pub samples: usize,
+    /// The edits that don't resynchronize within this number of max sizes are reported as not resynchronized.
+    #[arg(long, default_value_t = 64)]
+    pub limit_chunks: usize,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(Args)]
+pub struct FilterArgs {
+    /// Selects chunkers whose name matches any of the regular expressions. All chunkers are selected by default.
+    #[arg(short, long)]
+    pub filter: Vec<Regex>,
+    /// Skips chunkers whose name matches any of the regular expressions.
+    #[arg(short, long)]
+    pub exclude: Vec<Regex>,
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::cli::Cli;
+
+    #[test]
+    fn should_reject_empty_edits() {
+        for size in ["0", "0.5B", "0KB"] {
+            let args = ["cdc-comparison", "generate", "--max-edit-size", size];
+            assert!(Cli::try_parse_from(args).is_err(), "{}", size);
+        }
+        assert!(Cli::try_parse_from(["cdc-comparison", "generate", "--max-edit-size", "1"]).is_ok());
+    }
+}
@@ -0,0 +1,184 @@
+use std::marker::PhantomData;
+
+use crate::hashes::{RollingHash, RollingHashBuilder};
+
+/// The parameters of a reflected CRC, like in the catalogue https://reveng.sourceforge.io/crc-catalogue/all.htm
+#[derive(Copy, Clone, Debug)]
+pub struct CrcParams {
+    /// The reflected polynomial.
+    pub poly: u64,
+    pub init: u64,
+    pub xorout: u64,
+}
+
+/// CRC-32C (Castagnoli), the checksum of iSCSI, ext4 and the SSE4.2 `crc32` instruction.
+pub const CRC32C: CrcParams = CrcParams { poly: 0x82F63B78, init: 0xFFFFFFFF, xorout: 0xFFFFFFFF };
+/// CRC-64/XZ, the checksum of xz. It uses the ECMA-182 polynomial, like the `crc64.ECMA` table of Go.
+pub const CRC64_XZ: CrcParams =
+    CrcParams { poly: 0xC96C5795D7870F42, init: 0xFFFFFFFFFFFFFFFF, xorout: 0xFFFFFFFFFFFFFFFF };
+
+fn crc_table(poly: u64) -> [u64; 256] {
+    let mut table = [0; 256];
+    for (b, entry) in table.iter_mut().enumerate() {
+        let mut crc = b as u64;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ poly } else { crc >> 1 };
+        }
+        *entry = crc;
+    }
+    table
+}
+
+fn append_byte(table: &[u64; 256], crc: u64, b: u8) -> u64 {
+    (crc >> 8) ^ table[((crc ^ b as u64) & 0xff) as usize]
+}
+
+/// The CRC of the bytes, which is used to check the rolling CRC.
+pub fn checksum(params: CrcParams, bytes: &[u8]) -> u64 {
+    let table = crc_table(params.poly);
+    bytes.iter().fold(params.init, |crc, &b| append_byte(&table, crc, b)) ^ params.xorout
+}
+
+/// The rolling CRC of the window. The digest is the same as the [checksum] of the bytes in the window.
+///
+/// The CRC without the init and the xorout values is linear, so the byte that leaves the window
+/// is removed by adding the CRC of the byte followed by `window_size-1` zero bytes.
+/// The init and the xorout values only add a constant for the window size, which is the CRC of the zero bytes.
+pub struct CrcHashBuilder<T> {
+    table: [u64; 256],
+    out: [u64; 256],
+    zero_window_crc: u64,
+    window_size: usize,
+    width: PhantomData<T>,
+}
+
+impl<T> CrcHashBuilder<T> {
+    fn with_params(params: CrcParams, window_size: usize) -> Self {
+        let table = crc_table(params.poly);
+        let mut out = [0; 256];
+        for (b, entry) in out.iter_mut().enumerate() {
+            *entry = (1..window_size).fold(append_byte(&table, 0, b as u8), |crc, _| append_byte(&table, crc, 0));
+        }
+        let zero_window_crc = checksum(params, &vec![0; window_size]);
+        Self { table, out, zero_window_crc, window_size, width: PhantomData }
+    }
+}
+
+impl CrcHashBuilder<u32> {
+    pub fn crc32c(window_size: usize) -> Self {
+        Self::with_params(CRC32C, window_size)
+    }
+}
+
+impl CrcHashBuilder<u64> {
+    pub fn crc64_xz(window_size: usize) -> Self {
+        Self::with_params(CRC64_XZ, window_size)
+    }
+}
+
+impl RollingHashBuilder<u32> for CrcHashBuilder<u32> {
+    type RH<'a> = CrcHash<'a, u32>;
+
+    fn prepare_bytes_count(&self) -> usize {
+        self.window_size
+    }
+
+    fn new_hash(&self, buffer: &[u8]) -> Self::RH<'_> {
+        CrcHash::new(self, buffer)
+    }
+}
+
+impl RollingHashBuilder<u64> for CrcHashBuilder<u64> {
+    type RH<'a> = CrcHash<'a, u64>;
+
+    fn prepare_bytes_count(&self) -> usize {
+        self.window_size
+    }
+
+    fn new_hash(&self, buffer: &[u8]) -> Self::RH<'_> {
+        CrcHash::new(self, buffer)
+    }
+}
+
+pub struct CrcHash<'a, T> {
+    builder: &'a CrcHashBuilder<T>,
+    /// The CRC of the window without the init and the xorout values.
+    crc: u64,
+    window: Vec<u8>,
+    window_idx: usize,
+}
+
+impl<'a, T> CrcHash<'a, T> {
+    fn new(builder: &'a CrcHashBuilder<T>, buffer: &[u8]) -> Self {
+        let mut hash = Self { builder, crc: 0, window: vec![0; builder.window_size], window_idx: 0 };
+
+        for new_byte in buffer {
+            hash.roll_crc(*new_byte);
+        }
+
+        hash
+    }
+
+    fn roll_crc(&mut self, new_byte: u8) {
+        let old_byte = self.window[self.window_idx];
+        self.window[self.window_idx] = new_byte;
+        self.window_idx = (self.window_idx + 1) % self.builder.window_size;
+        self.crc ^= self.builder.out[old_byte as usize];
+        self.crc = append_byte(&self.builder.table, self.crc, new_byte);
+    }
+}
+
+impl<'a> RollingHash<'a, u32> for CrcHash<'a, u32> {
+    fn roll(&mut self, new_byte: u8) {
+        self.roll_crc(new_byte)
+    }
+
+    fn digest(&self) -> u32 {
+        (self.crc ^ self.builder.zero_window_crc) as u32
+    }
+}
+
+impl<'a> RollingHash<'a, u64> for CrcHash<'a, u64> {
+    fn roll(&mut self, new_byte: u8) {
+        self.roll_crc(new_byte)
+    }
+
+    fn digest(&self) -> u64 {
+        self.crc ^ self.builder.zero_window_crc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::hashes::crc::{checksum, CrcHashBuilder, CRC32C, CRC64_XZ};
+    use crate::hashes::{RollingHash, RollingHashBuilder};
+
+    #[test]
+    fn should_match_the_check_values() {
+        assert_eq!(checksum(CRC32C, b"123456789"), 0xE3069283);
+        assert_eq!(checksum(CRC64_XZ, b"123456789"), 0x995DC9BBDF1939FA);
+        assert_eq!(CrcHashBuilder::crc32c(9).new_hash(b"123456789").digest(), 0xE3069283);
+        assert_eq!(CrcHashBuilder::crc64_xz(9).new_hash(b"123456789").digest(), 0x995DC9BBDF1939FA);
+    }
+
+    #[test]
+    fn should_be_the_checksum_of_the_window() {
+        let mut data = vec![0u8; 1000];
+        ChaCha20Rng::seed_from_u64(19).fill(data.as_mut_slice());
+        let window_size = 48;
+        let crc32 = CrcHashBuilder::crc32c(window_size);
+        let crc64 = CrcHashBuilder::crc64_xz(window_size);
+        let mut hash32 = crc32.new_hash(&data[..window_size]);
+        let mut hash64 = crc64.new_hash(&data[..window_size]);
+        for end in window_size..data.len() {
+            let window = &data[end - window_size..end];
+            assert_eq!(hash32.digest() as u64, checksum(CRC32C, window));
+            assert_eq!(hash64.digest(), checksum(CRC64_XZ, window));
+            hash32.roll(data[end]);
+            hash64.roll(data[end]);
+        }
+    }
+}
@@ -1,11 +1,15 @@
 pub mod adler32;
 pub mod buzhash;
+pub mod crc;
 pub mod gearhash;
 pub mod polynomial_hash;
+pub mod rabin_karp;
 pub mod right_gearhash;
+pub mod rollsum;
+pub mod table_analysis;
 pub mod tables;
 
-pub trait RollingHashBuilder<T> {
+pub trait RollingHashBuilder<T>: Send + Sync {
     type RH<'a>: RollingHash<'a, T>
     where
         Self: 'a,
@@ -9,6 +9,7 @@ struct Tables {
 }
 
 impl Tables {
+    #[allow(clippy::needless_range_loop)]
     fn new(pol: Pol, window_size: usize) -> Tables {
         let mut out = [Pol::ZERO; 256];
         let mut mods = [Pol::ZERO; 256];
@@ -114,7 +115,7 @@ impl<'a> PolynomialHash<'a> {
 impl<'a> RollingHash<'a, u64> for PolynomialHash<'a> {
     fn roll(&mut self, new_byte: u8) {
         let old_byte = self.replace_and_return_oldest_window_byte(new_byte);
-        self.digest = self.digest ^ self.builder.tables.out[old_byte as usize].value();
+        self.digest ^= self.builder.tables.out[old_byte as usize].value();
         let index = self.digest >> self.builder.pol_shift;
         self.digest = ((self.digest << 8) | (new_byte as u64)) ^ self.builder.tables.mods[index as usize].value();
     }
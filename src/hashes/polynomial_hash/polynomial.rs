@@ -25,6 +25,7 @@
 // OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::hashes::tables::keyed_rng;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use std::cmp::Ordering;
@@ -48,7 +49,16 @@ impl Pol {
     /// c.f. Michael O. Rabin (1981): "Fingerprinting by Random Polynomials", page 4.
     /// If no polynomial could be found in one million tries, the function panics.
     pub fn generate_random_from_seed(seed: u64) -> Pol {
-        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        Self::generate_random_from_rng(&mut ChaCha20Rng::seed_from_u64(seed))
+    }
+
+    /// Returns an irreducible polynomial like [Pol::generate_random_from_seed],
+    /// but the random values are derived from the secret key, see [keyed_rng].
+    pub fn generate_random_from_key(key: &[u8]) -> Pol {
+        Self::generate_random_from_rng(&mut keyed_rng(key, b"polynomial"))
+    }
+
+    fn generate_random_from_rng(rng: &mut ChaCha20Rng) -> Pol {
         for _ in 0..1_000_000 {
             let mut f: Pol = Pol(rng.gen::<u64>());
             // mask away bits above bit 53
@@ -69,7 +79,7 @@ impl Pol {
 
     /// Returns the degree of the polynomial x. If x is zero, -1 is returned.
     pub fn deg(&self) -> i32 {
-        63 as i32 - self.0.leading_zeros() as i32
+        63_i32 - self.0.leading_zeros() as i32
     }
 
     /// Computes the Greatest Common Divisor x and f.
@@ -191,6 +201,7 @@ impl PartialOrd<u64> for Pol {
     }
 }
 
+#[allow(clippy::suspicious_arithmetic_impl)]
 impl Add for Pol {
     type Output = Self;
 
@@ -229,7 +240,7 @@ impl Mul for Pol {
                 if x & Pol(1 << 63) != 0 {
                     panic!("multiplication would overflow u64")
                 }
-                return x << 1;
+                x << 1
             }
             (x, y) => {
                 let mut res = Pol(0);
@@ -0,0 +1,103 @@
+use crate::hashes::{RollingHash, RollingHashBuilder};
+use crate::util::unsigned_integer::UnsignedInteger;
+
+/// The Rabin-Karp hash `c_0*B^(w-1) + c_1*B^(w-2) + ... + c_(w-1)` of the window modulo the width of the type.
+/// A new byte is added with `h*B + c`, and the byte that leaves the window is removed with `c_out*B^w`.
+///
+/// The base must be odd, otherwise the oldest bytes are shifted out of the lowest bits.
+/// The highest bits depend on all bytes of the window, but the lowest bits only depend on the lowest bits
+/// of the bytes, like in the Gear hash.
+pub struct RabinKarpBuilder<T: UnsignedInteger> {
+    base: T,
+    /// `B^w`, which multiplies the byte that leaves the window.
+    base_pow_window: T,
+    window_size: usize,
+}
+
+impl<T: UnsignedInteger> RabinKarpBuilder<T> {
+    pub fn new(base: T, window_size: usize) -> Self {
+        assert!(base & T::one() == T::one(), "The base must be odd");
+        let base_pow_window = (0..window_size).fold(T::one(), |pow, _| pow.wrapping_mul(&base));
+        Self { base, base_pow_window, window_size }
+    }
+}
+
+impl<T: UnsignedInteger> RollingHashBuilder<T> for RabinKarpBuilder<T> {
+    type RH<'a>
+        = RabinKarpHash<'a, T>
+    where
+        T: 'a;
+
+    fn prepare_bytes_count(&self) -> usize {
+        self.window_size
+    }
+
+    fn new_hash(&self, buffer: &[u8]) -> Self::RH<'_> {
+        RabinKarpHash::new(self, buffer)
+    }
+}
+
+pub struct RabinKarpHash<'a, T: UnsignedInteger> {
+    builder: &'a RabinKarpBuilder<T>,
+    digest: T,
+    window: Vec<u8>,
+    window_idx: usize,
+}
+
+impl<'a, T: UnsignedInteger> RabinKarpHash<'a, T> {
+    fn new(builder: &'a RabinKarpBuilder<T>, buffer: &[u8]) -> Self {
+        let mut hash = Self { builder, digest: T::zero(), window: vec![0; builder.window_size], window_idx: 0 };
+        for new_byte in buffer {
+            hash.roll(*new_byte);
+        }
+        hash
+    }
+}
+
+impl<'a, T: UnsignedInteger> RollingHash<'a, T> for RabinKarpHash<'a, T> {
+    fn roll(&mut self, new_byte: u8) {
+        let old_byte = self.window[self.window_idx];
+        self.window[self.window_idx] = new_byte;
+        self.window_idx = (self.window_idx + 1) % self.builder.window_size;
+        let added = self.digest.wrapping_mul(&self.builder.base).wrapping_add(&T::from_u64_truncated(new_byte as u64));
+        let removed = T::from_u64_truncated(old_byte as u64).wrapping_mul(&self.builder.base_pow_window);
+        self.digest = added.wrapping_sub(&removed);
+    }
+
+    fn digest(&self) -> T {
+        self.digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::hashes::rabin_karp::RabinKarpBuilder;
+    use crate::hashes::{RollingHash, RollingHashBuilder};
+    use crate::util::unsigned_integer::UnsignedInteger;
+
+    fn hash_of_window<T: UnsignedInteger>(window: &[u8], base: T) -> T {
+        window.iter().fold(T::zero(), |h, &c| h.wrapping_mul(&base).wrapping_add(&T::from_u64_truncated(c as u64)))
+    }
+
+    fn check_rolling<T: UnsignedInteger>(data: &[u8], base: T) {
+        let window_size = 32;
+        let builder = RabinKarpBuilder::new(base, window_size);
+        let mut hash = builder.new_hash(&data[..window_size]);
+        for end in window_size..data.len() {
+            assert!(hash.digest() == hash_of_window(&data[end - window_size..end], base));
+            hash.roll(data[end]);
+        }
+    }
+
+    #[test]
+    fn should_be_the_hash_of_the_window() {
+        let mut data = vec![0u8; 1000];
+        ChaCha20Rng::seed_from_u64(20).fill(data.as_mut_slice());
+        check_rolling::<u32>(&data, 0x01000193);
+        check_rolling::<u64>(&data, 0x100000001B3);
+        check_rolling::<u128>(&data, 0x100000001B3);
+    }
+}
@@ -0,0 +1,144 @@
+// This code is ported from the https://github.com/bup/bup/blob/main/lib/bup/bupsplit.c
+//
+// Copyright 2011 Avery Pennarun. All rights reserved.
+// Copyright (c) 2023, melgenek
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions
+// are met:
+//
+//    1. Redistributions of source code must retain the above copyright
+//       notice, this list of conditions and the following disclaimer.
+//
+//    2. Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in
+//       the documentation and/or other materials provided with the
+//       distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY AVERY PENNARUN ``AS IS'' AND ANY
+// EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL <COPYRIGHT HOLDER> OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! The rsync rolling checksum as it is used by bup.
+//!
+//! The two 16 bit sums of the rsync checksum are kept in 32 bit counters that overflow like the `unsigned` of C.
+//! Every byte is offset by `CHAR_OFFSET`, and the window is initially filled with zero bytes,
+//! so the sums of an empty input are not zero.
+use crate::hashes::{RollingHash, RollingHashBuilder};
+
+pub const WINDOW_SIZE: usize = 64;
+const CHAR_OFFSET: u32 = 31;
+
+pub struct RollsumBuilder;
+
+impl RollingHashBuilder<u32> for RollsumBuilder {
+    type RH<'a> = Rollsum;
+
+    fn prepare_bytes_count(&self) -> usize {
+        WINDOW_SIZE
+    }
+
+    fn new_hash(&self, buffer: &[u8]) -> Self::RH<'_> {
+        let mut hash = Rollsum::new();
+        for &byte in buffer {
+            hash.roll(byte);
+        }
+        hash
+    }
+}
+
+#[derive(Clone)]
+pub struct Rollsum {
+    s1: u32,
+    s2: u32,
+    window: [u8; WINDOW_SIZE],
+    window_idx: usize,
+}
+
+impl Rollsum {
+    pub fn new() -> Self {
+        Self {
+            s1: WINDOW_SIZE as u32 * CHAR_OFFSET,
+            s2: WINDOW_SIZE as u32 * (WINDOW_SIZE as u32 - 1) * CHAR_OFFSET,
+            window: [0; WINDOW_SIZE],
+            window_idx: 0,
+        }
+    }
+
+    /// The sum that bup checks for the split points.
+    pub fn s2(&self) -> u32 {
+        self.s2
+    }
+
+    /// The `rollsum_sum` of bup, which is the digest of `buf[offset..len]` rolled into a new checksum.
+    pub fn sum(buf: &[u8], offset: usize, len: usize) -> u32 {
+        RollsumBuilder.new_hash(&buf[offset..len]).digest()
+    }
+}
+
+impl Default for Rollsum {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> RollingHash<'a, u32> for Rollsum {
+    fn roll(&mut self, new_byte: u8) {
+        let old_byte = self.window[self.window_idx];
+        self.s1 = self.s1.wrapping_add(u32::from(new_byte)).wrapping_sub(u32::from(old_byte));
+        self.s2 = self.s2.wrapping_add(self.s1).wrapping_sub(WINDOW_SIZE as u32 * (u32::from(old_byte) + CHAR_OFFSET));
+        self.window[self.window_idx] = new_byte;
+        self.window_idx = (self.window_idx + 1) % WINDOW_SIZE;
+    }
+
+    fn digest(&self) -> u32 {
+        (self.s1 << 16) | (self.s2 & 0xffff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::hashes::rollsum::{Rollsum, WINDOW_SIZE};
+    use crate::hashes::RollingHash;
+
+    #[test]
+    fn should_start_with_the_sums_of_a_window_of_zeros() {
+        assert_eq!(Rollsum::new().digest(), (1984 << 16) | (124992 & 0xffff));
+        assert_eq!(Rollsum::sum(&[0; 1000], 0, 1000), Rollsum::new().digest());
+    }
+
+    // The digests of `rollsum_sum` for the whole buffers, computed with a Python transcription of bupsplit.c.
+    #[test]
+    fn should_compute_the_digests_of_bup() {
+        assert_eq!(Rollsum::sum(b"", 0, 0), 0x07c0e840);
+        assert_eq!(Rollsum::sum(b"abcdefghijklmnopqrstuvwxyz", 0, 26), 0x12df78ac);
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        assert_eq!(Rollsum::sum(&bytes, 0, bytes.len()), 0x3fa0aae0);
+    }
+
+    // The `bupsplit_selftest` of bup.
+    #[test]
+    fn should_depend_only_on_the_window() {
+        let mut buf = vec![0u8; 100000];
+        ChaCha20Rng::seed_from_u64(1).fill(buf.as_mut_slice());
+        let len = buf.len();
+
+        assert_eq!(Rollsum::sum(&buf, 0, len), Rollsum::sum(&buf, 1, len));
+        assert_eq!(
+            Rollsum::sum(&buf, len - WINDOW_SIZE * 5 / 2, len - WINDOW_SIZE),
+            Rollsum::sum(&buf, 0, len - WINDOW_SIZE)
+        );
+        assert_eq!(Rollsum::sum(&buf, 0, WINDOW_SIZE + 3), Rollsum::sum(&buf, 3, WINDOW_SIZE + 3));
+    }
+}
@@ -0,0 +1,221 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::chunkers::ported::borg::{self, BORG_TABLE};
+use crate::chunkers::ported::casync::{self, CASYNC_TABLE};
+use crate::chunkers::ported::fast_cdc2016::FAST_CDC_2016_TABLE;
+use crate::chunkers::ported::fast_cdc2020::GEAR_LS;
+use crate::chunkers::ported::google_stadia_cdc::GEAR;
+use crate::chunkers::ported::ronomon::{ronomon64_table, RONOMON_TABLE};
+use crate::hashes::buzhash::BuzHashBuilder;
+use crate::hashes::gearhash::GearHashBuilder;
+use crate::hashes::right_gearhash::RightGearHashBuilder;
+use crate::hashes::tables::{buz_table, sha256_u128_table, sha256_u32_table, sha256_u64_table};
+use crate::hashes::{RollingHash, RollingHashBuilder};
+use crate::util::mask_builder::{create_simple_mask, create_spread_mask, MaskBuilder};
+use crate::util::unsigned_integer::UnsignedInteger;
+
+/// The amount of work that is spent on the statistics that are measured on random input.
+#[derive(Copy, Clone)]
+pub struct AnalysisSettings {
+    /// The window of the Buzhash tables that are not tied to a chunker.
+    pub window: usize,
+    /// The number of the mask bits, so the expected split probability is `2^-mask_bits`.
+    pub mask_bits: u32,
+    /// The number of the single bit flips for the avalanche.
+    pub samples: usize,
+    /// The length of the random input for the split probability.
+    pub bytes: usize,
+}
+
+/// The quality of a lookup table of a rolling hash.
+/// The table properties don't depend on the hash, while the avalanche and the split ratio do.
+pub struct TableReport {
+    pub table: &'static str,
+    pub hash: String,
+    /// The largest difference between the count of the ones in a bit position and a half of the table.
+    pub max_bit_imbalance: usize,
+    /// The largest absolute correlation between two bit positions of the table values.
+    pub max_bit_correlation: f64,
+    /// The average share of the digest bits that change when one bit of a byte in the window is flipped.
+    /// It is 0.5 for an ideal hash.
+    pub avalanche: f64,
+    /// The largest difference between the flip probability of a digest bit and 0.5.
+    pub max_avalanche_bias: f64,
+    /// The observed split probability on random input divided by the probability of the mask.
+    pub split_ratio: f64,
+}
+
+/// Analyzes every table that the chunkers use, together with the hash that the table is used with.
+/// The Buzhash and the right Gear digests are matched with the simple mask, and the Gear digests with the spread mask.
+pub fn analyze_tables(settings: AnalysisSettings) -> Vec<TableReport> {
+    let window = settings.window;
+    vec![
+        analyze_buzhash("sha256_u32_table", sha256_u32_table(), window, settings),
+        analyze_buzhash("buz_table u32", buz_table::<u32>(), window, settings),
+        analyze_buzhash("sha256_u64_table", sha256_u64_table(), window, settings),
+        analyze_buzhash("buz_table u64", buz_table::<u64>(), window, settings),
+        analyze_buzhash("sha256_u128_table", sha256_u128_table(), window, settings),
+        analyze_buzhash("buz_table u128", buz_table::<u128>(), window, settings),
+        analyze_buzhash("CASYNC_TABLE", CASYNC_TABLE, casync::WINDOW_SIZE, settings),
+        analyze_buzhash("BORG_TABLE", BORG_TABLE, borg::WINDOW_SIZE, settings),
+        analyze_gear("sha256_u64_table", sha256_u64_table(), settings),
+        analyze_gear("buz_table u64", buz_table::<u64>(), settings),
+        analyze_gear("FAST_CDC_2016_TABLE", FAST_CDC_2016_TABLE, settings),
+        analyze_gear("FastCDC 2020 GEAR_LS", GEAR_LS, settings),
+        analyze_gear("Stadia GEAR", GEAR, settings),
+        analyze_right_gear("RONOMON_TABLE", RONOMON_TABLE, settings),
+        analyze_right_gear("ronomon64_table", ronomon64_table(), settings),
+    ]
+}
+
+fn analyze_buzhash<T: UnsignedInteger>(
+    name: &'static str,
+    table: [T; 256],
+    window: usize,
+    settings: AnalysisSettings,
+) -> TableReport {
+    let builder = BuzHashBuilder::new(table, window);
+    let hash = format!("Buzhash{} {}", T::bits_count(), window);
+    analyze_table(name, hash, &table, &builder, window, Box::new(create_simple_mask), settings)
+}
+
+/// A byte affects the Gear digest only until it is shifted out, so the window is the width of the digest.
+fn analyze_gear<T: UnsignedInteger>(name: &'static str, table: [T; 256], settings: AnalysisSettings) -> TableReport {
+    let builder = GearHashBuilder::new(table);
+    let hash = format!("Gear{}", T::bits_count());
+    analyze_table(name, hash, &table, &builder, T::bits_count(), Box::new(create_spread_mask), settings)
+}
+
+fn analyze_right_gear<T: UnsignedInteger>(
+    name: &'static str,
+    table: [T; 256],
+    settings: AnalysisSettings,
+) -> TableReport {
+    let builder = RightGearHashBuilder::new(table);
+    let hash = format!("RightGear{}", T::bits_count());
+    analyze_table(name, hash, &table, &builder, T::bits_count(), Box::new(create_simple_mask), settings)
+}
+
+pub fn analyze_table<T: UnsignedInteger, H: RollingHashBuilder<T>>(
+    name: &'static str,
+    hash: String,
+    table: &[T; 256],
+    hash_builder: &H,
+    window: usize,
+    mask_builder: MaskBuilder<T>,
+    settings: AnalysisSettings,
+) -> TableReport {
+    let (avalanche, max_avalanche_bias) = avalanche(hash_builder, window, settings.samples);
+    TableReport {
+        table: name,
+        hash,
+        max_bit_imbalance: max_bit_imbalance(table),
+        max_bit_correlation: max_bit_correlation(table),
+        avalanche,
+        max_avalanche_bias,
+        split_ratio: split_ratio(hash_builder, mask_builder(1 << settings.mask_bits), settings.bytes),
+    }
+}
+
+fn bit<T: UnsignedInteger>(value: T, index: usize) -> bool {
+    (value >> index) & T::one() != T::zero()
+}
+
+fn max_bit_imbalance<T: UnsignedInteger>(table: &[T; 256]) -> usize {
+    (0..T::bits_count())
+        .map(|index| table.iter().filter(|&&value| bit(value, index)).count().abs_diff(table.len() / 2))
+        .max()
+        .unwrap_or(0)
+}
+
+/// The phi coefficient of every pair of bit positions.
+/// The constant bits, like the highest bit of the right Gear tables, are left to the imbalance.
+fn max_bit_correlation<T: UnsignedInteger>(table: &[T; 256]) -> f64 {
+    let n = table.len() as f64;
+    let ones = (0..T::bits_count())
+        .map(|index| table.iter().filter(|&&value| bit(value, index)).count() as f64)
+        .collect::<Vec<_>>();
+    let mut result: f64 = 0.0;
+    for i in 0..T::bits_count() {
+        for j in (i + 1)..T::bits_count() {
+            let both = table.iter().filter(|&&value| bit(value, i) && bit(value, j)).count() as f64;
+            let variance = ones[i] * (n - ones[i]) * ones[j] * (n - ones[j]);
+            if variance > 0.0 {
+                result = result.max(((n * both - ones[i] * ones[j]) / variance.sqrt()).abs());
+            }
+        }
+    }
+    result
+}
+
+/// Flips a random bit of a random byte in a random window, and compares the digests of the windows.
+fn avalanche<T: UnsignedInteger, H: RollingHashBuilder<T>>(
+    hash_builder: &H,
+    window: usize,
+    samples: usize,
+) -> (f64, f64) {
+    let mut rng = ChaCha20Rng::seed_from_u64(22);
+    let mut flips = vec![0usize; T::bits_count()];
+    let mut buf = vec![0u8; window];
+    for _ in 0..samples {
+        rng.fill(buf.as_mut_slice());
+        let digest = hash_builder.new_hash(&buf).digest();
+        buf[rng.gen_range(0..window)] ^= 1 << rng.gen_range(0..8);
+        let changed = digest ^ hash_builder.new_hash(&buf).digest();
+        for (index, count) in flips.iter_mut().enumerate() {
+            if bit(changed, index) {
+                *count += 1;
+            }
+        }
+    }
+    let probabilities = flips.iter().map(|&count| count as f64 / samples as f64).collect::<Vec<_>>();
+    let avalanche = probabilities.iter().sum::<f64>() / probabilities.len() as f64;
+    let max_bias = probabilities.iter().map(|p| (p - 0.5).abs()).fold(0.0, f64::max);
+    (avalanche, max_bias)
+}
+
+fn split_ratio<T: UnsignedInteger, H: RollingHashBuilder<T>>(hash_builder: &H, mask: T, bytes: usize) -> f64 {
+    let mut data = vec![0u8; bytes];
+    ChaCha20Rng::seed_from_u64(23).fill(data.as_mut_slice());
+    let prepare_bytes_count = hash_builder.prepare_bytes_count();
+    let mut hash = hash_builder.new_hash(&data[..prepare_bytes_count]);
+    let mut splits = 0;
+    for &byte in &data[prepare_bytes_count..] {
+        hash.roll(byte);
+        if hash.digest() & mask == T::zero() {
+            splits += 1;
+        }
+    }
+    let mask_bits = (0..T::bits_count()).filter(|&index| bit(mask, index)).count() as i32;
+    let expected = (data.len() - prepare_bytes_count) as f64 / 2f64.powi(mask_bits);
+    splits as f64 / expected
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hashes::table_analysis::{analyze_tables, AnalysisSettings};
+
+    #[test]
+    fn should_report_the_balanced_tables() {
+        let settings = AnalysisSettings { window: 64, mask_bits: 8, samples: 2000, bytes: 1024 * 1024 };
+        let reports = analyze_tables(settings);
+        for report in &reports {
+            let name = format!("{} {}", report.table, report.hash);
+            assert!(report.max_bit_correlation < 0.5, "{} {}", name, report.max_bit_correlation);
+            if report.table.starts_with("buz_table") {
+                assert_eq!(report.max_bit_imbalance, 0, "{}", name);
+            }
+            // The lowest Gear bits depend on the last few bytes only, so the split ratio is up to the table there.
+            if report.hash.starts_with("Buzhash") {
+                assert!((report.avalanche - 0.5).abs() < 0.02, "{} {}", name, report.avalanche);
+                assert!((report.split_ratio - 1.0).abs() < 0.1, "{} {}", name, report.split_ratio);
+            }
+        }
+        let sha256 = reports.iter().find(|report| report.table == "sha256_u32_table").unwrap();
+        assert!(sha256.max_bit_imbalance > 0);
+        // The lowest bit of the shifted table is always zero, so a mask with that bit matches twice as often.
+        let shifted = reports.iter().find(|report| report.table == "FastCDC 2020 GEAR_LS").unwrap();
+        assert_eq!(shifted.max_bit_imbalance, 128);
+    }
+}
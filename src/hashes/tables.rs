@@ -4,6 +4,7 @@ use rand::seq::SliceRandom;
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 use ring::digest::{Context, SHA256};
+use ring::hmac;
 use std::io::Cursor;
 
 pub fn sha256_u128_table() -> [u128; 256] {
@@ -50,11 +51,30 @@ pub fn sha256_u32_table() -> [u32; 256] {
 
 // http://www.serve.net/buz/Notes.1st.year/HTML/C6/rand.012.html
 pub fn buz_table<T: UnsignedInteger>() -> [T; 256] {
-    let mut rng = ChaCha20Rng::seed_from_u64(1);
+    balanced_table(&mut ChaCha20Rng::seed_from_u64(1))
+}
+
+/// The balanced table like [buz_table], but the random numbers are derived from the secret key.
+/// The chunk lengths of the fixed tables can fingerprint known files,
+/// while the chunks of a keyed table can't be predicted without the key.
+/// The table is suitable for all table driven hashes: Buzhash, Gear and right Gear.
+pub fn keyed_table<T: UnsignedInteger>(key: &[u8]) -> [T; 256] {
+    balanced_table(&mut keyed_rng(key, b"table"))
+}
+
+/// A random number generator that is seeded with the HMAC-SHA256 of the purpose with the secret key.
+/// Different purposes give independent generators for the same key.
+pub fn keyed_rng(key: &[u8], purpose: &[u8]) -> ChaCha20Rng {
+    let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), purpose);
+    ChaCha20Rng::from_seed(tag.as_ref().try_into().expect("The HMAC-SHA256 tag has 32 bytes"))
+}
+
+/// Every bit position is set in exactly a half of the values.
+fn balanced_table<T: UnsignedInteger>(rng: &mut ChaCha20Rng) -> [T; 256] {
     let mut result = [T::zero(); 256];
     let mut indices = (0..=255).collect::<Vec<usize>>();
     for _ in 0..=T::signed_bits_count() {
-        indices.shuffle(&mut rng);
+        indices.shuffle(rng);
         for j in 0..=127 {
             result[indices[j]] = (result[indices[j]] << 1) | T::one();
         }
@@ -64,3 +84,19 @@ pub fn buz_table<T: UnsignedInteger>() -> [T; 256] {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::hashes::tables::{buz_table, keyed_table};
+
+    #[test]
+    fn should_derive_balanced_tables_from_the_key() {
+        let table = keyed_table::<u64>(b"secret");
+        assert!(table == keyed_table::<u64>(b"secret"));
+        assert!(table != keyed_table::<u64>(b"secret2"));
+        assert!(table != buz_table::<u64>());
+        for bit in 0..64 {
+            assert_eq!(table.iter().filter(|&&value| value >> bit & 1 == 1).count(), 128);
+        }
+    }
+}
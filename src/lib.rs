@@ -0,0 +1,35 @@
+//! Content defined chunking algorithms that are compared by the benchmark.
+//!
+//! The chunkers are built from spec strings and applied to any [std::io::Read] source:
+//!
+//! ```
+//! use cdc_comparison::{build_chunker, ChunkSizes, ChunkStream};
+//!
+//! let sizes = ChunkSizes::new(2 * 1024, 8 * 1024, 64 * 1024);
+//! let chunker = build_chunker("fastcdc2020:nc=2", sizes).unwrap();
+//! let data = vec![7u8; 100 * 1024];
+//! let mut stream = ChunkStream::new(data.as_slice(), chunker.as_ref(), sizes);
+//! let mut total = 0;
+//! while let Some(chunk) = stream.next_chunk() {
+//!     total += chunk.unwrap().data.len();
+//! }
+//! assert_eq!(total, data.len());
+//! ```
+//!
+//! The root of the crate re-exports the stable API.
+//! The modules expose the individual chunkers and hashes for the experiments.
+
+pub mod chunkers;
+pub mod hashes;
+pub mod util;
+
+pub use chunkers::registry::{build_chunker, ChunkerSpec, SpecError};
+pub use chunkers::Chunker;
+#[cfg(feature = "async")]
+pub use util::async_chunk_stream::AsyncChunkStream;
+pub use util::boundary::{Boundary, HashedBoundary};
+pub use util::chunk_sizes::ChunkSizes;
+pub use util::chunk_stream::{Chunk, ChunkIter, ChunkRef, ChunkStream};
+pub use util::mapped_files::{MappedFiles, SegmentChunks};
+pub use util::push_chunker::{PushBoundaries, PushChunker};
+pub use util::slice_chunks::SliceChunks;
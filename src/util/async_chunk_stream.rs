@@ -0,0 +1,179 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::chunkers::Chunker;
+use crate::util::chunk_sizes::ChunkSizes;
+use crate::util::chunk_stream::{next_chunk_length, Chunk};
+use crate::util::MB;
+
+/// The number of bytes that are searched for the split points before the stream yields to the executor.
+/// A single search covers at most `max_size` bytes, so the stream never blocks for longer than that.
+pub const YIELD_AFTER_BYTES: usize = 4 * MB;
+
+/// Splits an async source into the same chunks as the [crate::util::chunk_stream::ChunkStream] does.
+///
+/// The search for a split point runs on the polling task. Every poll searches for one split point only,
+/// and the stream yields to the executor after every [YIELD_AFTER_BYTES] searched bytes,
+/// so that a source that is always ready doesn't hold the executor thread.
+pub struct AsyncChunkStream<'a, R: AsyncRead + Unpin> {
+    /// Buffer of data from source for finding cut points.
+    buffer: Vec<u8>,
+    /// Start of the bytes in the `buffer` that are not chunked yet.
+    start: usize,
+    /// End of the relevant bytes in the `buffer`.
+    end: usize,
+    /// Source from which data is read into `buffer`.
+    source: R,
+    /// Number of bytes chunked so far.
+    processed: usize,
+    /// True when the source produces no more data.
+    eof: bool,
+    /// Number of bytes searched since the stream yielded to the executor last time.
+    searched: usize,
+    chunker: &'a dyn Chunker,
+    chunk_sizes: ChunkSizes,
+}
+
+impl<'a, R: AsyncRead + Unpin> AsyncChunkStream<'a, R> {
+    /// Creates the stream with a buffer of twice the max chunk size,
+    /// so that the pending bytes are moved to the start of the buffer at most once per `max_size` chunked bytes.
+    pub fn new(source: R, chunker: &'a dyn Chunker, chunk_sizes: ChunkSizes) -> Self {
+        Self::with_buffer_size(source, chunker, chunk_sizes, 2 * chunk_sizes.max_size())
+    }
+
+    /// Creates the stream with a refill buffer of `buffer_size` bytes, which is at least the max chunk size.
+    pub fn with_buffer_size(source: R, chunker: &'a dyn Chunker, chunk_sizes: ChunkSizes, buffer_size: usize) -> Self {
+        assert!(buffer_size >= chunk_sizes.max_size());
+        Self {
+            buffer: vec![0_u8; buffer_size],
+            start: 0,
+            end: 0,
+            source,
+            processed: 0,
+            eof: false,
+            searched: 0,
+            chunker,
+            chunk_sizes,
+        }
+    }
+
+    /// Fill the buffer with data from the source, so that it has at least `max_size` bytes unless the source ends.
+    fn poll_fill_buffer(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        if self.buffer.len() - self.start < self.chunk_sizes.max_size() {
+            self.buffer.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        while !self.eof && self.end - self.start < self.chunk_sizes.max_size() {
+            let mut read_buf = ReadBuf::new(&mut self.buffer[self.end..]);
+            match Pin::new(&mut self.source).poll_read(cx, &mut read_buf) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Ready(Ok(())) if read_buf.filled().is_empty() => self.eof = true,
+                Poll::Ready(Ok(())) => self.end += read_buf.filled().len(),
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<'a, R: AsyncRead + Unpin> Stream for AsyncChunkStream<'a, R> {
+    type Item = std::io::Result<Chunk>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match this.poll_fill_buffer(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(Err(err)) => return Poll::Ready(Some(Err(err))),
+            Poll::Ready(Ok(())) => {}
+        }
+        if this.searched >= YIELD_AFTER_BYTES {
+            this.searched = 0;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let remaining = &this.buffer[this.start..this.end];
+        let chunk_length = next_chunk_length(this.chunker, &this.chunk_sizes, remaining);
+        if chunk_length == 0 {
+            return Poll::Ready(None);
+        }
+        let chunk = Chunk { offset: this.processed, length: chunk_length, data: remaining[..chunk_length].to_vec() };
+        this.searched += remaining.len().min(this.chunk_sizes.max_size());
+        this.processed += chunk_length;
+        this.start += chunk_length;
+        Poll::Ready(Some(Ok(chunk)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures_core::Stream;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use tokio::io::{AsyncRead, ReadBuf};
+
+    use crate::chunkers::registry::{set_test_key, ChunkerSpec, EXAMPLE_SPECS};
+    use crate::util::async_chunk_stream::AsyncChunkStream;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::chunk_stream::{Chunk, ChunkStream};
+    use crate::util::KB;
+
+    /// Returns at most 5000 bytes per read and is not ready on every other read.
+    struct SlowAsyncRead<'a> {
+        data: &'a [u8],
+        ready: bool,
+    }
+
+    impl AsyncRead for SlowAsyncRead<'_> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            self.ready = !self.ready;
+            if !self.ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let count = buf.remaining().min(self.data.len()).min(5000);
+            buf.put_slice(&self.data[..count]);
+            self.data = &self.data[count..];
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn should_produce_the_same_chunks_as_the_sync_stream() {
+        let mut data = vec![0u8; 3 * 1024 * KB + 5];
+        ChaCha20Rng::seed_from_u64(4).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(64 * KB, 128 * KB, 256 * KB);
+
+        set_test_key();
+        for spec in EXAMPLE_SPECS {
+            // A new chunker for every stream, so that the stateful chunkers don't remember the chunks of another one.
+            let new_chunker = || spec.parse::<ChunkerSpec>().unwrap().build(sizes).unwrap();
+            let expected = ChunkStream::new(data.as_slice(), new_chunker().as_ref(), sizes)
+                .into_owned()
+                .collect::<std::io::Result<Vec<Chunk>>>()
+                .unwrap();
+
+            for buffer_size in [2 * sizes.max_size(), sizes.max_size()] {
+                let source = SlowAsyncRead { data: &data, ready: false };
+                let chunker = new_chunker();
+                let mut stream = AsyncChunkStream::with_buffer_size(source, chunker.as_ref(), sizes, buffer_size);
+                let mut chunks = Vec::new();
+                while let Some(chunk) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+                    chunks.push(chunk.unwrap());
+                }
+                assert_eq!(chunks, expected, "{} {}", spec, buffer_size);
+            }
+        }
+    }
+}
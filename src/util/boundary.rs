@@ -0,0 +1,37 @@
+use crate::util::chunk_stream::ChunkRef;
+use crate::util::sha256_digest;
+
+/// The raw SHA-256 digest of the chunk bytes.
+pub type Sha256Digest = [u8; 32];
+
+/// A chunk without its bytes.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Boundary {
+    /// Starting byte position within the source.
+    pub offset: usize,
+    /// Length of the chunk in bytes.
+    pub length: usize,
+}
+
+/// A chunk without its bytes, but with their digest.
+/// The digest is computed from the buffer of the stream before the bytes are dropped.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct HashedBoundary {
+    /// Starting byte position within the source.
+    pub offset: usize,
+    /// Length of the chunk in bytes.
+    pub length: usize,
+    pub digest: Sha256Digest,
+}
+
+impl From<ChunkRef<'_>> for Boundary {
+    fn from(chunk: ChunkRef<'_>) -> Self {
+        Boundary { offset: chunk.offset, length: chunk.length }
+    }
+}
+
+impl From<ChunkRef<'_>> for HashedBoundary {
+    fn from(chunk: ChunkRef<'_>) -> Self {
+        HashedBoundary { offset: chunk.offset, length: chunk.length, digest: sha256_digest(chunk.data) }
+    }
+}
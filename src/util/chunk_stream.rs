@@ -1,7 +1,14 @@
 use std::io::Read;
+use std::marker::PhantomData;
 
 use crate::chunkers::Chunker;
+use crate::util::boundary::{Boundary, HashedBoundary};
 use crate::util::chunk_sizes::ChunkSizes;
+use crate::util::MB;
+
+/// The smallest refill buffer. The buffer is moved to its beginning once per `buffer - max_size` bytes,
+/// so a buffer that is much bigger than the max chunk size makes the move negligible.
+pub const MIN_BUFFER_SIZE: usize = 16 * MB;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Chunk {
@@ -13,26 +20,74 @@ pub struct Chunk {
     pub data: Vec<u8>,
 }
 
+/// A chunk that borrows its bytes from the buffer of the [ChunkStream].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ChunkRef<'b> {
+    /// Starting byte position within the source.
+    pub offset: usize,
+    /// Length of the chunk in bytes.
+    pub length: usize,
+    /// Source bytes contained in this chunk. They are valid until the next chunk is requested.
+    pub data: &'b [u8],
+}
+
+impl From<ChunkRef<'_>> for Chunk {
+    fn from(chunk: ChunkRef<'_>) -> Self {
+        Chunk { offset: chunk.offset, length: chunk.length, data: chunk.data.to_vec() }
+    }
+}
+
+/// Returns the length of the next chunk of the `remaining` bytes, or 0 when there are no bytes left.
+/// The chunker gets at most `max_size` bytes and the tail that is not longer than `min_size` is a chunk.
+pub(crate) fn next_chunk_length(chunker: &dyn Chunker, chunk_sizes: &ChunkSizes, remaining: &[u8]) -> usize {
+    if remaining.len() <= chunk_sizes.min_size() {
+        return remaining.len();
+    }
+    let window = remaining.len().min(chunk_sizes.max_size());
+    let chunk_length = chunker.find_split_point(&remaining[..window], chunk_sizes);
+    if chunk_length > window {
+        panic!(
+            "The chunk size is bigger than the buffer: {} > {}. The splitter implementation is wrong.",
+            chunk_length, window
+        );
+    }
+    chunk_length
+}
+
+/// Splits the source into chunks without copying them.
+/// [ChunkStream::next_chunk] returns the chunks as slices of a large refill buffer.
+/// The stream can be turned into an iterator of owned chunks with [ChunkStream::into_owned],
+/// or of the boundaries only with [ChunkStream::into_boundaries] and [ChunkStream::into_hashed_boundaries].
 pub struct ChunkStream<'a, R: Read> {
     /// Buffer of data from source for finding cut points.
     buffer: Vec<u8>,
-    /// Number of relevant bytes in the `buffer`.
-    length: usize,
+    /// Start of the bytes in the `buffer` that are not chunked yet.
+    start: usize,
+    /// End of the relevant bytes in the `buffer`.
+    end: usize,
     /// Source from which data is read into `buffer`.
     source: R,
-    /// Number of bytes read from the source so far.
+    /// Number of bytes chunked so far.
     processed: usize,
     /// True when the source produces no more data.
     eof: bool,
-    chunker: &'a Box<dyn Chunker>,
+    chunker: &'a dyn Chunker,
     chunk_sizes: ChunkSizes,
 }
 
 impl<'a, R: Read> ChunkStream<'a, R> {
-    pub fn new(source: R, chunker: &'a Box<dyn Chunker>, chunk_sizes: ChunkSizes) -> Self {
+    pub fn new(source: R, chunker: &'a dyn Chunker, chunk_sizes: ChunkSizes) -> Self {
+        let buffer_size = MIN_BUFFER_SIZE.max(4 * chunk_sizes.max_size());
+        Self::with_buffer_size(source, chunker, chunk_sizes, buffer_size)
+    }
+
+    /// Creates the stream with a refill buffer of `buffer_size` bytes, which is at least the max chunk size.
+    pub fn with_buffer_size(source: R, chunker: &'a dyn Chunker, chunk_sizes: ChunkSizes, buffer_size: usize) -> Self {
+        assert!(buffer_size >= chunk_sizes.max_size());
         Self {
-            buffer: vec![0_u8; chunk_sizes.max_size()],
-            length: 0,
+            buffer: vec![0_u8; buffer_size],
+            start: 0,
+            end: 0,
             source,
             eof: false,
             processed: 0,
@@ -41,55 +96,116 @@ impl<'a, R: Read> ChunkStream<'a, R> {
         }
     }
 
-    /// Fill the buffer with data from the source
+    /// Fill the buffer with data from the source, so that it has at least `max_size` bytes unless the source ends.
     fn fill_buffer(&mut self) -> std::io::Result<()> {
-        while !self.eof && self.length < self.chunk_sizes.max_size() {
-            let bytes_read = self.source.read(&mut self.buffer[self.length..])?;
+        if self.eof || self.end - self.start >= self.chunk_sizes.max_size() {
+            return Ok(());
+        }
+        if self.buffer.len() - self.start < self.chunk_sizes.max_size() {
+            self.buffer.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        while !self.eof && self.end < self.buffer.len() {
+            let bytes_read = self.source.read(&mut self.buffer[self.end..])?;
             if bytes_read == 0 {
                 self.eof = true;
             } else {
-                self.length += bytes_read;
+                self.end += bytes_read;
             }
         }
         Ok(())
     }
 
-    fn cut_chunk(&mut self, chunk_length: usize) -> Vec<u8> {
-        let mut data: Vec<u8> = vec![0; chunk_length];
-        data.copy_from_slice(&self.buffer[..chunk_length]);
+    /// Returns the next chunk, or `None` when the source is exhausted.
+    pub fn next_chunk(&mut self) -> Option<std::io::Result<ChunkRef<'_>>> {
+        if let Err(err) = self.fill_buffer() {
+            return Some(Err(err));
+        }
+        let remaining = &self.buffer[self.start..self.end];
+        let chunk_length = next_chunk_length(self.chunker, &self.chunk_sizes, remaining);
+        if chunk_length == 0 {
+            None
+        } else {
+            let offset = self.processed;
+            let data = &remaining[..chunk_length];
+            self.processed += chunk_length;
+            self.start += chunk_length;
+            Some(Ok(ChunkRef { offset, length: chunk_length, data }))
+        }
+    }
+
+    /// Turns the stream into an iterator that copies every chunk into its own [Chunk].
+    pub fn into_owned(self) -> ChunkIter<'a, R, Chunk> {
+        ChunkIter { stream: self, item: PhantomData }
+    }
+
+    /// Turns the stream into an iterator over the offsets and lengths of the chunks.
+    pub fn into_boundaries(self) -> ChunkIter<'a, R, Boundary> {
+        ChunkIter { stream: self, item: PhantomData }
+    }
 
-        self.buffer.copy_within(chunk_length.., 0);
-        self.length -= chunk_length;
-        data
+    /// Turns the stream into an iterator over the offsets, lengths and digests of the chunks.
+    pub fn into_hashed_boundaries(self) -> ChunkIter<'a, R, HashedBoundary> {
+        ChunkIter { stream: self, item: PhantomData }
     }
 }
 
-impl<'a, R: Read> Iterator for ChunkStream<'a, R> {
-    type Item = std::io::Result<Chunk>;
-
-    fn next(&mut self) -> Option<std::io::Result<Chunk>> {
-        match self.fill_buffer() {
-            Err(err) => Some(Err(err)),
-            Ok(_) => {
-                let chunk_length = if self.length <= self.chunk_sizes.min_size() {
-                    self.length
-                } else {
-                    self.chunker.find_split_point(&self.buffer[..self.length], &self.chunk_sizes)
-                };
-                if chunk_length == 0 {
-                    None
-                } else if chunk_length > self.length {
-                    panic!(
-                        "The chunk size is bigger than the buffer: {} > {}. The splitter implementation is wrong.",
-                        chunk_length, self.length
-                    );
-                } else {
-                    let offset = self.processed;
-                    self.processed += chunk_length;
-                    let data = self.cut_chunk(chunk_length);
-                    Some(Ok(Chunk { offset, length: chunk_length, data }))
-                }
-            }
+/// An iterator that converts every borrowed chunk of the stream into `C`.
+pub struct ChunkIter<'a, R: Read, C> {
+    stream: ChunkStream<'a, R>,
+    item: PhantomData<C>,
+}
+
+impl<'a, R: Read, C: for<'b> From<ChunkRef<'b>>> Iterator for ChunkIter<'a, R, C> {
+    type Item = std::io::Result<C>;
+
+    fn next(&mut self) -> Option<std::io::Result<C>> {
+        self.stream.next_chunk().map(|result| result.map(C::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::registry::ChunkerSpec;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::chunk_stream::{Chunk, ChunkStream};
+    use crate::util::KB;
+
+    /// Returns at most 1000 bytes per read, so that the buffer is refilled many times.
+    struct SlowRead<'a>(&'a [u8]);
+
+    impl Read for SlowRead<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let count = buf.len().min(self.0.len()).min(1000);
+            buf[..count].copy_from_slice(&self.0[..count]);
+            self.0 = &self.0[count..];
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn should_not_depend_on_buffer_size() {
+        let mut data = vec![0u8; 3 * 1024 * KB];
+        ChaCha20Rng::seed_from_u64(1).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 64 * KB);
+        let chunker = "gear:width=64".parse::<ChunkerSpec>().unwrap().build(sizes).unwrap();
+
+        let expected = ChunkStream::new(data.as_slice(), chunker.as_ref(), sizes)
+            .into_owned()
+            .collect::<std::io::Result<Vec<Chunk>>>()
+            .unwrap();
+        for buffer_size in [64 * KB, 64 * KB + 1, 100 * KB] {
+            let stream = ChunkStream::with_buffer_size(SlowRead(&data), chunker.as_ref(), sizes, buffer_size);
+            let chunks = stream.into_owned().collect::<std::io::Result<Vec<Chunk>>>().unwrap();
+            assert_eq!(chunks, expected);
         }
+        assert!(expected.len() > 100);
+        assert_eq!(expected.iter().map(|chunk| chunk.data.as_slice()).collect::<Vec<_>>().concat(), data);
     }
 }
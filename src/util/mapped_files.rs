@@ -0,0 +1,170 @@
+use std::fs::File;
+use std::path::PathBuf;
+
+use memmap2::Mmap;
+
+use crate::chunkers::Chunker;
+use crate::util::chunk_sizes::ChunkSizes;
+use crate::util::chunk_stream::{next_chunk_length, ChunkRef};
+
+/// Files that are mapped into memory once and chunked as a single input without file boundaries,
+/// like the [crate::util::multi_file_dir::MultiFileRead] does.
+/// The mapping is read-only, so it can be shared between threads and chunked by many chunkers at once.
+pub struct MappedFiles {
+    maps: Vec<Mmap>,
+}
+
+impl MappedFiles {
+    /// Maps the files in the given order. The files must not be changed while they are mapped.
+    pub fn open(files: Vec<PathBuf>) -> std::io::Result<MappedFiles> {
+        let mut maps = Vec::with_capacity(files.len());
+        for path in files {
+            let file = File::open(path)?;
+            // Empty files can't be mapped, and they don't add any bytes to the input.
+            if file.metadata()?.len() > 0 {
+                // Safety: the benchmark inputs are not modified while the benchmark runs.
+                maps.push(unsafe { Mmap::map(&file)? });
+            }
+        }
+        Ok(MappedFiles { maps })
+    }
+
+    /// The total size of the files in bytes.
+    pub fn len(&self) -> usize {
+        self.maps.iter().map(|map| map.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+
+    pub fn chunks<'a>(&'a self, chunker: &'a dyn Chunker, chunk_sizes: ChunkSizes) -> SegmentChunks<'a> {
+        SegmentChunks::new(self.maps.iter().map(|map| &map[..]).collect(), chunker, chunk_sizes)
+    }
+}
+
+/// Splits a sequence of in-memory segments as if they were one contiguous input.
+///
+/// The chunker gets slices of the segments directly. Only when less than `max_size` bytes are left
+/// in a segment, the bytes up to `max_size` are copied from it and the following segments into a seam buffer.
+/// This makes the boundaries the same as the ones of the [crate::util::chunk_stream::ChunkStream]
+/// over the concatenated segments.
+pub struct SegmentChunks<'a> {
+    segments: Vec<&'a [u8]>,
+    /// The segment with the next byte to chunk, and the position of the byte in it.
+    segment: usize,
+    position: usize,
+    /// Number of bytes chunked so far.
+    processed: usize,
+    /// The bytes around the end of a segment, for a chunk that may span several segments.
+    seam: Vec<u8>,
+    chunker: &'a dyn Chunker,
+    chunk_sizes: ChunkSizes,
+}
+
+impl<'a> SegmentChunks<'a> {
+    pub fn new(segments: Vec<&'a [u8]>, chunker: &'a dyn Chunker, chunk_sizes: ChunkSizes) -> Self {
+        Self {
+            segments,
+            segment: 0,
+            position: 0,
+            processed: 0,
+            seam: Vec::with_capacity(chunk_sizes.max_size()),
+            chunker,
+            chunk_sizes,
+        }
+    }
+
+    /// Returns the next chunk, or `None` when all the segments are chunked.
+    /// The chunk borrows either a segment or the seam buffer, so it is valid until the next chunk is requested.
+    pub fn next_chunk(&mut self) -> Option<ChunkRef<'_>> {
+        while self.segment < self.segments.len() && self.position == self.segments[self.segment].len() {
+            self.segment += 1;
+            self.position = 0;
+        }
+        if self.segment == self.segments.len() {
+            return None;
+        }
+        let max_size = self.chunk_sizes.max_size();
+        let current: &'a [u8] = &self.segments[self.segment][self.position..];
+        let offset = self.processed;
+        if current.len() >= max_size || self.segment + 1 == self.segments.len() {
+            let length = next_chunk_length(self.chunker, &self.chunk_sizes, current);
+            self.advance(length);
+            return Some(ChunkRef { offset, length, data: &current[..length] });
+        }
+
+        self.seam.clear();
+        for segment in std::iter::once(current).chain(self.segments[self.segment + 1..].iter().copied()) {
+            let count = segment.len().min(max_size - self.seam.len());
+            self.seam.extend_from_slice(&segment[..count]);
+            if self.seam.len() == max_size {
+                break;
+            }
+        }
+        let length = next_chunk_length(self.chunker, &self.chunk_sizes, &self.seam);
+        self.advance(length);
+        Some(ChunkRef { offset, length, data: &self.seam[..length] })
+    }
+
+    /// Moves the position by `length` bytes, possibly into the next segments.
+    fn advance(&mut self, mut length: usize) {
+        self.processed += length;
+        while length > 0 {
+            let available = self.segments[self.segment].len() - self.position;
+            if length < available {
+                self.position += length;
+                return;
+            }
+            length -= available;
+            self.segment += 1;
+            self.position = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::registry::ChunkerSpec;
+    use crate::util::boundary::HashedBoundary;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::chunk_stream::ChunkStream;
+    use crate::util::mapped_files::SegmentChunks;
+    use crate::util::KB;
+
+    #[test]
+    fn should_find_the_same_boundaries_as_the_stream_over_concatenated_segments() {
+        let mut rng = ChaCha20Rng::seed_from_u64(5);
+        let mut data = vec![0u8; 2 * 1024 * KB + 99];
+        rng.fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 64 * KB);
+
+        // Segments from empty to several max sizes long, so that some chunks span many segments.
+        let mut segments = Vec::new();
+        let mut remaining = data.as_slice();
+        while !remaining.is_empty() {
+            let segment_size = remaining.len().min(rng.gen_range(0..200 * KB) >> rng.gen_range(0..12));
+            let (segment, rest) = remaining.split_at(segment_size);
+            segments.push(segment);
+            remaining = rest;
+        }
+
+        for spec in ["fixed", "fastcdc2020", "restic", "buzhash_reg:width=64,window=64"] {
+            let chunker = spec.parse::<ChunkerSpec>().unwrap().build(sizes).unwrap();
+            let expected = ChunkStream::new(data.as_slice(), chunker.as_ref(), sizes)
+                .into_hashed_boundaries()
+                .collect::<std::io::Result<Vec<HashedBoundary>>>()
+                .unwrap();
+
+            let mut chunks = SegmentChunks::new(segments.clone(), chunker.as_ref(), sizes);
+            let mut boundaries = Vec::new();
+            while let Some(chunk) = chunks.next_chunk() {
+                boundaries.push(HashedBoundary::from(chunk));
+            }
+            assert_eq!(boundaries, expected, "{}", spec);
+        }
+    }
+}
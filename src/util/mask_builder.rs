@@ -6,6 +6,13 @@ use rand_chacha::ChaCha20Rng;
 use crate::util::unsigned_integer::UnsignedInteger;
 
 pub type MaskBuilder<T> = Box<dyn Fn(usize) -> T>;
+/// The probability that a digest of random input matches the mask.
+pub type MaskProbability<T> = Box<dyn Fn(T) -> f64>;
+
+/// Every bit of the mask halves the probability that `digest & mask == 0`.
+pub fn simple_mask_probability<T: UnsignedInteger>(mask: T) -> f64 {
+    0.5f64.powi(mask.count_ones() as i32)
+}
 
 pub fn create_simple_mask<T: UnsignedInteger>(target_size: usize) -> T {
     let bits_count = logarithm2(target_size as u32);
@@ -15,8 +22,8 @@ pub fn create_simple_mask<T: UnsignedInteger>(target_size: usize) -> T {
 pub fn create_spread_mask<T: UnsignedInteger>(target_size: usize) -> T {
     let bits_count = logarithm2(target_size as u32);
     let mut rng = ChaCha20Rng::seed_from_u64(6543833);
-    let mut bit_indices: Vec<usize> = (0..T::bits_count()).into_iter().collect();
+    let mut bit_indices: Vec<usize> = (0..T::bits_count()).collect();
     bit_indices.shuffle(&mut rng);
     let shift_indices = &bit_indices[0..bits_count as usize];
-    shift_indices.into_iter().fold(T::zero(), |num, idx| num | (T::one() << *idx))
+    shift_indices.iter().fold(T::zero(), |num, idx| num | (T::one() << *idx))
 }
@@ -6,10 +6,18 @@ use data_encoding::HEXLOWER;
 use ring::digest::{Context, SHA256};
 use walkdir::WalkDir;
 
+use crate::util::boundary::Sha256Digest;
+
+#[cfg(feature = "async")]
+pub mod async_chunk_stream;
+pub mod boundary;
 pub mod chunk_sizes;
 pub mod chunk_stream;
+pub mod mapped_files;
 pub mod mask_builder;
 pub mod multi_file_dir;
+pub mod push_chunker;
+pub mod slice_chunks;
 pub mod unsigned_integer;
 
 pub const KB: usize = 1024;
@@ -35,13 +43,17 @@ fn read_files_in_dir_sorted<P: AsRef<Path>>(dir: P) -> Vec<(PathBuf, u64)> {
 
 pub fn sha256(bytes: &[u8]) -> String {
     let mut context = Context::new(&SHA256);
-    context.update(&bytes);
+    context.update(bytes);
     HEXLOWER.encode(context.finish().as_ref())
 }
 
+pub fn sha256_digest(bytes: &[u8]) -> Sha256Digest {
+    ring::digest::digest(&SHA256, bytes).as_ref().try_into().expect("SHA-256 digest is 32 bytes")
+}
+
 pub fn sha256_file<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
     let mut context = Context::new(&SHA256);
-    let mut buffer = vec![0; 1 * MB];
+    let mut buffer = vec![0; MB];
     let mut file = File::open(path)?;
     loop {
         let count = file.read(&mut buffer)?;
@@ -73,3 +85,23 @@ pub fn size_to_str_f64(value: f64) -> String {
         format!("{:.2}MB", value / MB as f64)
     }
 }
+
+/// Parses sizes in the format produced by [size_to_str], e.g. `64KB`, `1.5MB` or plain bytes `512`.
+pub fn parse_size(value: &str) -> Result<usize, String> {
+    let value = value.trim();
+    let (number, multiplier) = if let Some(number) = value.strip_suffix("MB") {
+        (number, MB)
+    } else if let Some(number) = value.strip_suffix("KB") {
+        (number, KB)
+    } else if let Some(number) = value.strip_suffix('B') {
+        (number, 1)
+    } else {
+        (value, 1)
+    };
+    let number: f64 = number.trim().parse().map_err(|_| format!("Invalid size '{}'", value))?;
+    let bytes = (number * multiplier as f64) as usize;
+    if bytes == 0 {
+        return Err(format!("The size must be at least one byte: '{}'", value));
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+
+use crate::chunkers::Chunker;
+use crate::util::boundary::Boundary;
+use crate::util::chunk_sizes::ChunkSizes;
+use crate::util::chunk_stream::next_chunk_length;
+
+/// Splits data that arrives in pushes of any size, e.g. from a socket or a decoder.
+///
+/// No hash state is carried from one push to the next. The pushed bytes are buffered instead,
+/// and a split point is searched only when `max_size` bytes are pending or the input is finished,
+/// exactly like the [crate::util::chunk_stream::ChunkStream] does.
+/// This makes the boundaries byte-identical to the stream ones for every chunker.
+///
+/// At most `max_size` pending bytes are kept between the pushes, even when the boundaries of a push are not taken.
+/// The chunked bytes are dropped from the buffer only once there are `max_size` of them,
+/// so up to `2 * max_size` bytes are buffered.
+pub struct PushChunker<'a> {
+    chunker: &'a dyn Chunker,
+    chunk_sizes: ChunkSizes,
+    /// Pushed bytes. The ones that are not chunked yet start at `start`.
+    buffer: Vec<u8>,
+    start: usize,
+    /// Number of bytes chunked so far.
+    processed: usize,
+    /// The boundaries of the pushes whose iterators were dropped early. They come before the new ones.
+    queued: VecDeque<Boundary>,
+}
+
+impl<'a> PushChunker<'a> {
+    pub fn new(chunker: &'a dyn Chunker, chunk_sizes: ChunkSizes) -> Self {
+        Self {
+            chunker,
+            chunk_sizes,
+            buffer: Vec::with_capacity(2 * chunk_sizes.max_size()),
+            start: 0,
+            processed: 0,
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Pushes the bytes and returns the boundaries of the chunks that are complete.
+    /// When the iterator is dropped early, the rest of the bytes is chunked anyway,
+    /// and the boundaries that were not taken are returned by the next push or by [PushChunker::finish].
+    pub fn update<'p, 'b>(&'p mut self, bytes: &'b [u8]) -> PushBoundaries<'p, 'a, 'b> {
+        PushBoundaries { chunker: self, input: bytes }
+    }
+
+    /// Ends the input and returns the boundaries of the pending bytes.
+    pub fn finish(mut self) -> Vec<Boundary> {
+        let mut boundaries = Vec::from(std::mem::take(&mut self.queued));
+        boundaries.extend(std::iter::from_fn(|| self.cut()));
+        boundaries
+    }
+
+    fn pending(&self) -> usize {
+        self.buffer.len() - self.start
+    }
+
+    /// Moves the bytes from the input to the buffer until `max_size` bytes are pending.
+    fn fill(&mut self, input: &mut &[u8]) {
+        if self.start >= self.chunk_sizes.max_size() {
+            self.buffer.drain(..self.start);
+            self.start = 0;
+        }
+        let count = input.len().min(self.chunk_sizes.max_size().saturating_sub(self.pending()));
+        self.buffer.extend_from_slice(&input[..count]);
+        *input = &input[count..];
+    }
+
+    /// Cuts a chunk when `max_size` bytes are pending, so the input is empty once there is no chunk to cut.
+    fn next_boundary(&mut self, input: &mut &[u8]) -> Option<Boundary> {
+        self.fill(input);
+        if self.pending() < self.chunk_sizes.max_size() {
+            None
+        } else {
+            self.cut()
+        }
+    }
+
+    fn cut(&mut self) -> Option<Boundary> {
+        let length = next_chunk_length(self.chunker, &self.chunk_sizes, &self.buffer[self.start..]);
+        if length == 0 {
+            None
+        } else {
+            let offset = self.processed;
+            self.processed += length;
+            self.start += length;
+            Some(Boundary { offset, length })
+        }
+    }
+}
+
+/// The boundaries of the chunks that are complete after a push, see [PushChunker::update].
+pub struct PushBoundaries<'p, 'a, 'b> {
+    chunker: &'p mut PushChunker<'a>,
+    input: &'b [u8],
+}
+
+impl Iterator for PushBoundaries<'_, '_, '_> {
+    type Item = Boundary;
+
+    fn next(&mut self) -> Option<Boundary> {
+        match self.chunker.queued.pop_front() {
+            Some(boundary) => Some(boundary),
+            None => self.chunker.next_boundary(&mut self.input),
+        }
+    }
+}
+
+impl Drop for PushBoundaries<'_, '_, '_> {
+    fn drop(&mut self) {
+        while let Some(boundary) = self.chunker.next_boundary(&mut self.input) {
+            self.chunker.queued.push_back(boundary);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::registry::ChunkerSpec;
+    use crate::util::boundary::Boundary;
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::chunk_stream::ChunkStream;
+    use crate::util::push_chunker::PushChunker;
+    use crate::util::{KB, MB};
+
+    #[test]
+    fn should_find_the_same_boundaries_as_the_stream() {
+        let mut rng = ChaCha20Rng::seed_from_u64(3);
+        let mut data = vec![0u8; 2 * 1024 * KB + 17];
+        rng.fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 64 * KB);
+
+        for spec in ["fastcdc2020", "ronomon", "buzhash_reg:width=64,window=64", "adler32:window=64"] {
+            let chunker = spec.parse::<ChunkerSpec>().unwrap().build(sizes).unwrap();
+            let expected = ChunkStream::new(data.as_slice(), chunker.as_ref(), sizes)
+                .into_boundaries()
+                .collect::<std::io::Result<Vec<Boundary>>>()
+                .unwrap();
+
+            let mut push_chunker = PushChunker::new(chunker.as_ref(), sizes);
+            let mut boundaries = Vec::new();
+            let mut remaining = data.as_slice();
+            while !remaining.is_empty() {
+                let push_size = remaining.len().min(rng.gen_range(1..200 * KB));
+                let (push, rest) = remaining.split_at(push_size);
+                // Taking a single boundary leaves the rest of the push pending.
+                boundaries.extend(push_chunker.update(push).take(rng.gen_range(1..4)));
+                remaining = rest;
+            }
+            boundaries.extend(push_chunker.update(&[]));
+            boundaries.extend(push_chunker.finish());
+            assert_eq!(boundaries, expected, "{}", spec);
+        }
+    }
+
+    #[test]
+    fn should_keep_at_most_the_max_size_when_the_boundaries_are_not_taken() {
+        let mut data = vec![0u8; 8 * MB];
+        ChaCha20Rng::seed_from_u64(7).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 64 * KB);
+        let chunker = "gear:width=64".parse::<ChunkerSpec>().unwrap().build(sizes).unwrap();
+        let expected = ChunkStream::new(data.as_slice(), chunker.as_ref(), sizes)
+            .into_boundaries()
+            .collect::<std::io::Result<Vec<Boundary>>>()
+            .unwrap();
+
+        let mut push_chunker = PushChunker::new(chunker.as_ref(), sizes);
+        let mut boundaries = push_chunker.update(&data).take(1).collect::<Vec<_>>();
+        assert!(push_chunker.buffer.len() <= 2 * sizes.max_size(), "{}", push_chunker.buffer.len());
+        boundaries.extend(push_chunker.finish());
+        assert_eq!(boundaries, expected);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::chunkers::Chunker;
+use crate::util::chunk_sizes::ChunkSizes;
+use crate::util::chunk_stream::{next_chunk_length, ChunkRef};
+
+/// Splits data that is already in memory, e.g. a slice or a memory mapped file.
+/// The chunks borrow the input, so nothing is copied or buffered.
+/// The boundaries are the same as the ones of the [crate::util::chunk_stream::ChunkStream] over the same bytes.
+///
+/// The chunks can be mapped into [crate::util::boundary::Boundary] or [crate::util::boundary::HashedBoundary]
+/// when only the offsets, lengths and digests are needed.
+pub struct SliceChunks<'a> {
+    data: &'a [u8],
+    /// Number of bytes chunked so far.
+    processed: usize,
+    chunker: &'a dyn Chunker,
+    chunk_sizes: ChunkSizes,
+}
+
+impl<'a> SliceChunks<'a> {
+    pub fn new(data: &'a [u8], chunker: &'a dyn Chunker, chunk_sizes: ChunkSizes) -> Self {
+        Self { data, processed: 0, chunker, chunk_sizes }
+    }
+}
+
+impl<'a> Iterator for SliceChunks<'a> {
+    type Item = ChunkRef<'a>;
+
+    fn next(&mut self) -> Option<ChunkRef<'a>> {
+        let remaining = &self.data[self.processed..];
+        let chunk_length = next_chunk_length(self.chunker, &self.chunk_sizes, remaining);
+        if chunk_length == 0 {
+            None
+        } else {
+            let offset = self.processed;
+            self.processed += chunk_length;
+            Some(ChunkRef { offset, length: chunk_length, data: &remaining[..chunk_length] })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::chunkers::registry::ChunkerSpec;
+    use crate::util::boundary::{Boundary, HashedBoundary};
+    use crate::util::chunk_sizes::ChunkSizes;
+    use crate::util::chunk_stream::ChunkStream;
+    use crate::util::slice_chunks::SliceChunks;
+    use crate::util::{sha256_digest, KB};
+
+    #[test]
+    fn should_find_the_same_boundaries_as_the_stream() {
+        let mut data = vec![0u8; 2 * 1024 * KB + 123];
+        ChaCha20Rng::seed_from_u64(2).fill(data.as_mut_slice());
+        let sizes = ChunkSizes::new(4 * KB, 16 * KB, 64 * KB);
+        let chunker = "fastcdc2016".parse::<ChunkerSpec>().unwrap().build(sizes).unwrap();
+
+        let expected = ChunkStream::new(data.as_slice(), chunker.as_ref(), sizes)
+            .into_boundaries()
+            .collect::<std::io::Result<Vec<Boundary>>>()
+            .unwrap();
+        let boundaries = SliceChunks::new(&data, chunker.as_ref(), sizes).map(Boundary::from).collect::<Vec<_>>();
+        assert_eq!(boundaries, expected);
+
+        let hashed = SliceChunks::new(&data, chunker.as_ref(), sizes).map(HashedBoundary::from).collect::<Vec<_>>();
+        for (boundary, hashed) in boundaries.iter().zip(hashed) {
+            assert_eq!(hashed.digest, sha256_digest(&data[boundary.offset..boundary.offset + boundary.length]));
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt::{Binary, Display};
 use std::ops::{BitAnd, BitOr, BitXor, Shl, Shr, Sub};
 
-use num_traits::WrappingAdd;
+use num_traits::{WrappingAdd, WrappingMul, WrappingSub};
 
 pub trait UnsignedInteger:
     BitXor<Output = Self>
@@ -12,10 +12,14 @@ pub trait UnsignedInteger:
     + Shl<usize, Output = Self>
     + Shr<usize, Output = Self>
     + WrappingAdd
+    + WrappingMul
+    + WrappingSub
     + Binary
     + Display
     + Sized
     + Copy
+    + Send
+    + Sync
     + 'static
 {
     fn zero() -> Self;
@@ -25,6 +29,9 @@ pub trait UnsignedInteger:
         Self::bits_count() - 1
     }
     fn rotate_left(self, n: u32) -> Self;
+    fn count_ones(self) -> u32;
+    /// Keeps the lowest bits of the value that fit into the type.
+    fn from_u64_truncated(value: u64) -> Self;
 }
 
 impl UnsignedInteger for u32 {
@@ -43,6 +50,14 @@ impl UnsignedInteger for u32 {
     fn rotate_left(self, n: u32) -> Self {
         u32::rotate_left(self, n)
     }
+
+    fn count_ones(self) -> u32 {
+        u32::count_ones(self)
+    }
+
+    fn from_u64_truncated(value: u64) -> Self {
+        value as u32
+    }
 }
 
 impl UnsignedInteger for u64 {
@@ -61,6 +76,14 @@ impl UnsignedInteger for u64 {
     fn rotate_left(self, n: u32) -> Self {
         u64::rotate_left(self, n)
     }
+
+    fn count_ones(self) -> u32 {
+        u64::count_ones(self)
+    }
+
+    fn from_u64_truncated(value: u64) -> Self {
+        value
+    }
 }
 
 impl UnsignedInteger for u128 {
@@ -79,4 +102,12 @@ impl UnsignedInteger for u128 {
     fn rotate_left(self, n: u32) -> Self {
         u128::rotate_left(self, n)
     }
+
+    fn count_ones(self) -> u32 {
+        u128::count_ones(self)
+    }
+
+    fn from_u64_truncated(value: u64) -> Self {
+        value as u128
+    }
 }